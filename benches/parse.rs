@@ -0,0 +1,43 @@
+//! Parse time benchmark: `Parsers::parse_program` on generated files of growing length. The files are tokenized up
+//! front, only parsing is timed. Run with `cargo bench --bench parse`, the time per line should stay flat.
+
+use std::fmt::Write;
+
+use ZenBackend::{features::tokenizer::tokenize_file, parsers::Parsers};
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+
+/// A file of `lines` lines mixing declarations, conditions, maps, comments and text with `;` in it.
+fn generate(lines: usize) -> String {
+	let mut out = String::new();
+	for i in (0..lines).step_by(8) {
+		writeln!(out, "fonksiyon f{i}(a: sayı, b: sayı = 2)").unwrap();
+		writeln!(out, "\teğer a << b ve değil (a == {i}) ise").unwrap();
+		writeln!(out, "\t\tdöndür a * b + {i}").unwrap();
+		writeln!(out, "\tx = {{\"anahtar\": \"değer {{a}}\", \"liste\": [a, b]}}").unwrap();
+		writeln!(out, "\tdöndür a ^ 2 - (b / 3) % 7").unwrap();
+		writeln!(out, "y{i} = ({i} + 1) * 2 >= 3 veya yanlış").unwrap();
+		writeln!(out, "// yorum satırı {i}").unwrap();
+		writeln!(out, "m{i} = \"metin; noktalı virgül\"").unwrap();
+	}
+	out
+}
+
+fn parse(c: &mut Criterion) {
+	let mut group = c.benchmark_group("parse_program");
+	group.sample_size(10);
+	for lines in [2500, 5000, 10000] {
+		let tokens = tokenize_file(&generate(lines), 0, true).expect("the generated file is laid out correctly");
+		group.throughput(Throughput::Elements(lines as u64));
+		group.bench_function(format!("{lines} satır"), |b| {
+			b.iter_batched(
+				|| tokens.clone(),
+				|tokens| Parsers::parse_program(tokens).expect("the generated file parses"),
+				BatchSize::LargeInput,
+			)
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
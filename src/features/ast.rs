@@ -0,0 +1,237 @@
+//! Readable views of a parsed program, used by `zen run --printast`. Only the parsed tree is looked at, the scope
+//! pointers `BuildScopes` fills in later are left out.
+
+use crate::{
+	features::tokenizer::{Accessor, AssignmentMethod, Atom, InstructionEnum, Statement, TokenData, TokenTable, YieldInstructionEnum},
+	library::Types::{Object, ParameterData, RandomizerType, TimeUnit},
+	parsers::Parsers::Expression,
+};
+
+/// Draws `statements` as a tree, one instruction per row followed by the line it was written on.
+pub fn render(statements: &[Statement]) -> String {
+	let mut out = String::from("Program\n");
+	render_into(&mut out, statements, "");
+	out
+}
+
+fn render_into(out: &mut String, statements: &[Statement], prefix: &str) {
+	for (i, statement) in statements.iter().enumerate() {
+		let last = i + 1 == statements.len();
+		out.push_str(&format!(
+			"{prefix}{}{}  (satır {})\n",
+			if last { "└── " } else { "├── " },
+			describe(&statement.instr),
+			statement.span.line
+		));
+		render_into(out, &statement.body, &format!("{prefix}{}", if last { "    " } else { "│   " }));
+	}
+}
+
+/// The instruction's kind and its operands, e.g. `VariableDeclaration x += 1`.
+pub fn describe(instr: &InstructionEnum) -> String {
+	let list = |atoms: &[Atom]| atoms.iter().map(atom).collect::<Vec<_>>().join(", ");
+	let step = |step: &Option<Atom>| step.as_ref().map_or(String::new(), |x| format!(" adım {}", atom(x)));
+	match instr {
+		InstructionEnum::NoOp => "NoOp".to_owned(),
+		InstructionEnum::Print(atoms) => format!("Print {}", list(atoms)),
+		InstructionEnum::Type(atoms) => format!("Type {}", list(atoms)),
+		InstructionEnum::Wait { amount, unit } => format!("Wait {} {}", atom(amount), time_unit(unit)),
+		InstructionEnum::Repeat { repeat_count, .. } => format!("Repeat {}", atom(repeat_count)),
+		InstructionEnum::For { from, to, step: by, name, .. } => format!("For {name} = {} .. {}{}", atom(from), atom(to), step(by)),
+		InstructionEnum::ForIn { name, step: by, varname, .. } => format!("ForIn {varname} içinde {name}{}", step(by)),
+		InstructionEnum::WhileTrue { .. } => "WhileTrue".to_owned(),
+		InstructionEnum::RepeatUntil { condition, .. } => format!("RepeatUntil {}", atom(condition)),
+		InstructionEnum::RepeatWhile { condition, .. } => format!("RepeatWhile {}", atom(condition)),
+		InstructionEnum::IfBlock { condition, .. } => format!("IfBlock {}", atom(condition)),
+		InstructionEnum::ElifBlock { condition, .. } => format!("ElifBlock {}", atom(condition)),
+		InstructionEnum::ElseBlock { .. } => "ElseBlock".to_owned(),
+		InstructionEnum::Condition(_) => "Condition".to_owned(),
+		InstructionEnum::TryBlock { .. } => "TryBlock".to_owned(),
+		InstructionEnum::CatchBlock { name, .. } => match name {
+			Some(name) => format!("CatchBlock {name}"),
+			None => "CatchBlock".to_owned(),
+		},
+		InstructionEnum::FinallyBlock { .. } => "FinallyBlock".to_owned(),
+		InstructionEnum::TryCatch(_) => "TryCatch".to_owned(),
+		InstructionEnum::Function { name, args, .. } => {
+			format!("Function {name}({})", args.iter().map(parameter).collect::<Vec<_>>().join(", "))
+		}
+		InstructionEnum::CallFunction { name, args } => format!("CallFunction {name}({})", list(args)),
+		InstructionEnum::VariableDeclaration(name, value, method) => {
+			format!("VariableDeclaration {name} {} {}", assignment(method), atom(value))
+		}
+		InstructionEnum::IndexAssignment { name, path, value, method } => format!(
+			"IndexAssignment {name}{} {} {}",
+			path.iter().map(accessor).collect::<String>(),
+			assignment(method),
+			atom(value)
+		),
+		InstructionEnum::Import { path, namespace } => format!("Import {} -> {namespace}", text(path)),
+		InstructionEnum::Throw(value) => format!("Throw {}", atom(value)),
+		InstructionEnum::Test { name, .. } => format!("Test {}", text(name)),
+		InstructionEnum::Assert(value) => format!("Assert {}", atom(value)),
+		InstructionEnum::AssertEqual(left, right) => format!("AssertEqual {}, {}", atom(left), atom(right)),
+		InstructionEnum::Break => "Break".to_owned(),
+		InstructionEnum::Continue => "Continue".to_owned(),
+		InstructionEnum::Return(value) => format!("Return {}", atom(value)),
+	}
+}
+
+pub fn atom(atom: &Atom) -> String {
+	match atom {
+		Atom::Expression(expr) => expression(expr),
+		Atom::YieldInstruction(instr) => match instr {
+			YieldInstructionEnum::Input { quote, _type } => match _type {
+				Some(kind) => format!("girdi({}) {}", type_name(kind), expression(quote)),
+				None => format!("girdi {}", expression(quote)),
+			},
+			YieldInstructionEnum::Random { method, span } => {
+				let method = match method {
+					RandomizerType::Number => "sayı".to_owned(),
+					RandomizerType::Letter => "harf".to_owned(),
+					RandomizerType::Boolean { chance } => format!("ihtimal(%{})", expression(chance)),
+				};
+				match span {
+					Some((from, to)) => format!("rastgele {method} {}, {}", expression(from), expression(to)),
+					None => format!("rastgele {method}"),
+				}
+			}
+			YieldInstructionEnum::RandomVar(name) => format!("rastgele {name}"),
+			YieldInstructionEnum::CallFunction { name, args } => {
+				format!("{name}({})", args.iter().map(expression).collect::<Vec<_>>().join(", "))
+			}
+			YieldInstructionEnum::Index(name, path) => format!("{name}{}", path.iter().map(accessor).collect::<String>()),
+		},
+	}
+}
+
+/// Writes the expression back in Zen syntax. Operands that are operations themselves get parentheses, so the
+/// grouping the parser chose is visible.
+pub fn expression(expr: &Expression) -> String {
+	let operand = |expr: &Expression| match unspanned(expr) {
+		Expression::Value(_) | Expression::Map(_) | Expression::Format(_) => expression(expr),
+		_ => format!("({})", expression(expr)),
+	};
+	let binary = |lhs: &Expression, op: &str, rhs: &Expression| format!("{} {op} {}", operand(lhs), operand(rhs));
+	match expr {
+		Expression::Value(obj) => object(obj),
+		Expression::Not(inner) => format!("değil {}", operand(inner)),
+		Expression::Add(lhs, rhs) => binary(lhs, "+", rhs),
+		Expression::Sub(lhs, rhs) => binary(lhs, "-", rhs),
+		Expression::Mul(lhs, rhs) => binary(lhs, "*", rhs),
+		Expression::Div(lhs, rhs) => binary(lhs, "/", rhs),
+		Expression::Mod(lhs, rhs) => binary(lhs, "%", rhs),
+		Expression::Pow(lhs, rhs) => binary(lhs, "^", rhs),
+		Expression::LessThan(lhs, rhs) => binary(lhs, "<<", rhs),
+		Expression::GreaterThan(lhs, rhs) => binary(lhs, ">>", rhs),
+		Expression::LessThanOrEqual(lhs, rhs) => binary(lhs, "<=", rhs),
+		Expression::GreaterThanOrEqual(lhs, rhs) => binary(lhs, ">=", rhs),
+		Expression::Equal(lhs, rhs) => binary(lhs, "==", rhs),
+		Expression::NotEqual(lhs, rhs) => binary(lhs, "!=", rhs),
+		Expression::In(lhs, rhs) => binary(lhs, "içinde", rhs),
+		Expression::And(lhs, rhs) => binary(lhs, "ve", rhs),
+		Expression::Or(lhs, rhs) => binary(lhs, "veya", rhs),
+		Expression::Map(entries) => format!(
+			"{{{}}}",
+			entries
+				.iter()
+				.map(|(key, value)| format!("{}: {}", expression(key), expression(value)))
+				.collect::<Vec<_>>()
+				.join(", ")
+		),
+		Expression::Format(parts) => {
+			let parts = parts.iter().map(|part| match unspanned(part) {
+				Expression::Value(obj) if matches!(**obj, Object::Text(_)) => escape(&obj.to_string()).replace('{', "{{").replace('}', "}}"),
+				_ => format!("{{{}}}", expression(part)),
+			});
+			format!("\"{}\"", parts.collect::<String>())
+		}
+		Expression::Spanned(inner, _) => expression(inner),
+	}
+}
+
+fn unspanned(expr: &Expression) -> &Expression {
+	match expr {
+		Expression::Spanned(inner, _) => unspanned(inner),
+		_ => expr,
+	}
+}
+
+/// Literals as they would be written, `Display` of `Object` colors them and drops the quotes of texts.
+pub fn object(obj: &Object) -> String {
+	match obj {
+		Object::Number(number) => number.value.to_string(),
+		Object::Text(value) => text(&value.value),
+		Object::Bool(value) => if value.value { "doğru" } else { "yanlış" }.to_owned(),
+		Object::Variable(name) => name.clone(),
+		Object::Array(array) => format!("[{}]", array.value.iter().map(object).collect::<Vec<_>>().join(", ")),
+		Object::Map(map) => format!(
+			"{{{}}}",
+			map.value.iter().map(|(key, value)| format!("{}: {}", object(key), object(value))).collect::<Vec<_>>().join(", ")
+		),
+		Object::Null => "NIL".to_owned(),
+	}
+}
+
+fn text(value: &str) -> String {
+	format!("\"{}\"", escape(value))
+}
+
+fn escape(value: &str) -> String {
+	value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t")
+}
+
+fn accessor(accessor: &Accessor) -> String {
+	match accessor {
+		Accessor::Index(index) => format!("{{{}}}", expression(index)),
+		Accessor::Slice(from, to) => format!(
+			"{{{}:{}}}",
+			from.as_ref().map(expression).unwrap_or_default(),
+			to.as_ref().map(expression).unwrap_or_default()
+		),
+	}
+}
+
+pub fn parameter(parameter: &ParameterData) -> String {
+	let mut out = parameter.name.clone();
+	if let Some(kind) = &parameter.data_type {
+		out.push_str(&format!(": {}", type_name(kind)));
+	}
+	if let Some(default) = &parameter.default_value {
+		out.push_str(&format!(" = {}", expression(default)));
+	}
+	out
+}
+
+/// `main_types` matches template tokens without a slice, so the name comes from the kind.
+fn type_name(kind: &TokenData) -> &'static str {
+	match kind.token {
+		TokenTable::KeywordSayı => "sayı",
+		TokenTable::KeywordMetin => "metin",
+		TokenTable::KeywordMantıksal => "mantıksal",
+		_ => "?",
+	}
+}
+
+pub fn assignment(method: &AssignmentMethod) -> &'static str {
+	match method {
+		AssignmentMethod::Set => "=",
+		AssignmentMethod::Add => "+=",
+		AssignmentMethod::Sub => "-=",
+		AssignmentMethod::Mul => "*=",
+		AssignmentMethod::Div => "/=",
+	}
+}
+
+fn time_unit(unit: &TimeUnit) -> &'static str {
+	match unit {
+		TimeUnit::Millisecond => "salise",
+		TimeUnit::Second => "saniye",
+		TimeUnit::Minute => "dakika",
+		TimeUnit::Hour => "saat",
+		TimeUnit::Day => "gün",
+		TimeUnit::Week => "hafta",
+		TimeUnit::Month => "ay",
+		TimeUnit::Year => "yıl",
+	}
+}
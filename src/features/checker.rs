@@ -0,0 +1,462 @@
+//! `zen check`: finds mistakes in a parsed program without running it. Names are resolved the way `ScopeManager`
+//! resolves them at run time: blocks share the variables of the function or file they are in, functions only see
+//! their own variables, and functions are reachable from everywhere below where they are declared.
+
+use std::collections::{HashMap, HashSet};
+
+use miette::{NamedSource, Report, Severity};
+
+use crate::{
+	features::tokenizer::{Accessor, AssignmentMethod, Atom, InstructionEnum, Span, Statement, YieldInstructionEnum},
+	library::{
+		Error::{
+			CokFazlaArguman, DegiskenBulunamadı, DonguDisinda, EgerOlmadanDegilse, EksikArguman, ErisilemeyenKod, FonksiyonBulunamadı,
+			FonksiyonDisindaDondur, KullanilmayanDegisken,
+		},
+		Types::{Object, ParameterData, RandomizerType},
+	},
+	parsers::Parsers::Expression,
+	util::process::ParseSource,
+};
+
+/// Variables `DeclareGlobals` defines before any code runs, they are visible inside functions too.
+const GLOBALS: [&str; 2] = ["ekrangenişliği", "ekranyüksekliği"];
+
+/// Parses `source` and checks it. Syntax errors are returned as the error, findings are sorted by position.
+pub fn check(source: &str, filename: &str) -> miette::Result<Vec<Report>> {
+	let statements = ParseSource(source, filename, 0, true)?;
+	Ok(lint(&statements, source, filename))
+}
+
+/// Everything wrong with `statements` that can be seen without running them.
+pub fn lint(statements: &[Statement], source: &str, filename: &str) -> Vec<Report> {
+	let mut checker = Checker {
+		src: NamedSource::new(filename, source.to_owned()),
+		scopes: Vec::new(),
+		loops: 0,
+		functions: 0,
+		findings: Vec::new(),
+	};
+	checker.push(Kind::Function);
+	checker.block(statements);
+	checker.pop();
+
+	checker.findings.sort_by_key(|(start, _)| *start);
+	checker.findings.into_iter().map(|(_, report)| report).collect()
+}
+
+/// Whether any of `findings` is an error rather than a warning.
+pub fn has_errors(findings: &[Report]) -> bool {
+	findings.iter().any(|x| x.severity().unwrap_or(Severity::Error) == Severity::Error)
+}
+
+#[derive(PartialEq)]
+enum Kind {
+	/// The file itself or a function body, variables do not reach past it.
+	Function,
+	/// The body of `... içinde dolan` or `test`, it keeps its own variables but sees the ones around it.
+	Loop,
+	/// Any other block, the variables it declares belong to the scope around it.
+	Block,
+}
+
+struct Variable {
+	span: Span,
+	used: bool,
+	/// Loop variables and parameters are not reported when they are never read.
+	report_unused: bool,
+}
+
+struct Signature {
+	params: Vec<ParameterData>,
+	/// Set once the `fonksiyon` line is reached, calling it earlier fails at run time.
+	defined: bool,
+}
+
+struct Scope {
+	kind: Kind,
+	variables: HashMap<String, Variable>,
+	functions: HashMap<String, Signature>,
+	modules: HashSet<String>,
+}
+
+struct Checker {
+	src: NamedSource<String>,
+	scopes: Vec<Scope>,
+	/// Loops around the current statement, counted from the innermost function.
+	loops: usize,
+	functions: usize,
+	findings: Vec<(usize, Report)>,
+}
+
+impl Checker {
+	fn report(&mut self, span: Span, report: impl Into<Report>) {
+		self.findings.push((span.start, report.into()));
+	}
+
+	fn push(&mut self, kind: Kind) {
+		self.scopes.push(Scope {
+			kind,
+			variables: HashMap::new(),
+			functions: HashMap::new(),
+			modules: HashSet::new(),
+		});
+	}
+
+	fn pop(&mut self) {
+		let scope = self.scopes.pop().expect("every pop has a push");
+		let mut unused = scope.variables.into_iter().filter(|(_, x)| x.report_unused && !x.used).collect::<Vec<_>>();
+		unused.sort_by_key(|(_, x)| x.span.start);
+		for (name, variable) in unused {
+			let src = self.src.clone();
+			self.report(variable.span, KullanilmayanDegisken { src, bad_bit: variable.span.into(), name });
+		}
+	}
+
+	/// Scopes a variable lookup from the innermost scope goes through.
+	fn visible(&mut self) -> impl Iterator<Item = &mut Scope> {
+		let boundary = self.scopes.iter().rposition(|x| x.kind == Kind::Function).unwrap_or(0);
+		self.scopes[boundary..].iter_mut().rev()
+	}
+
+	fn declare(&mut self, name: &str, span: Span, report_unused: bool) {
+		let scope = self.scopes.iter_mut().rev().find(|x| x.kind != Kind::Block).expect("the file scope is never popped");
+		scope.variables.entry(name.to_owned()).or_insert(Variable {
+			span,
+			used: false,
+			report_unused: report_unused && !name.starts_with('_'),
+		});
+	}
+
+	/// Assigning to a variable that is already visible changes it instead of declaring a new one.
+	fn assign(&mut self, name: &str, span: Span) {
+		if name.contains('.') {
+			return self.read(name, span);
+		}
+		if !self.visible().any(|x| x.variables.contains_key(name)) {
+			self.declare(name, span, true);
+		}
+	}
+
+	fn read(&mut self, name: &str, span: Span) {
+		let head = name.split('.').next().unwrap_or(name);
+		if GLOBALS.contains(&head) || (name.contains('.') && self.scopes.iter().any(|x| x.modules.contains(head))) {
+			return;
+		}
+		if let Some(variable) = self.visible().find_map(|x| x.variables.get_mut(head)) {
+			variable.used = true;
+			return;
+		}
+		let src = self.src.clone();
+		self.report(span, DegiskenBulunamadı { src, bad_bit: span.into() });
+	}
+
+	fn call(&mut self, name: &str, args: usize, span: Span) {
+		if let Some((namespace, _)) = name.split_once('.')
+			&& self.scopes.iter().any(|x| x.modules.contains(namespace))
+		{
+			return;
+		}
+		let src = self.src.clone();
+		// A function declared further down can still be called from a function body that runs after it.
+		let mut crossed = false;
+		let mut found = None;
+		for scope in self.scopes.iter().rev() {
+			if let Some(signature) = scope.functions.get(name) {
+				found = (signature.defined || crossed).then(|| signature.params.clone());
+				break;
+			}
+			crossed |= scope.kind == Kind::Function;
+		}
+		let Some(params) = found else {
+			return self.report(span, FonksiyonBulunamadı { src, bad_bit: span.into() });
+		};
+		if args > params.len() {
+			let expected = Some(params.len());
+			self.report(span, CokFazlaArguman { src, bad_bit: span.into(), expected, got: Some(args) });
+		} else if let Some(missing) = params[args..].iter().find(|x| x.default_value.is_none()) {
+			let expected = Some(missing.name.clone());
+			self.report(span, EksikArguman { src, bad_bit: span.into(), expected });
+		}
+	}
+
+	fn block(&mut self, statements: &[Statement]) {
+		for statement in statements {
+			if let InstructionEnum::Function { name, args, .. } = &statement.instr {
+				let scope = self.scopes.last_mut().expect("blocks run inside a scope");
+				scope.functions.insert(name.clone(), Signature { params: args.clone(), defined: false });
+			}
+		}
+
+		let mut previous: Option<&InstructionEnum> = None;
+		let mut unreachable = false;
+		for (i, statement) in statements.iter().enumerate() {
+			if matches!(statement.instr, InstructionEnum::ElifBlock { .. } | InstructionEnum::ElseBlock { .. })
+				&& !matches!(previous, Some(InstructionEnum::IfBlock { .. } | InstructionEnum::ElifBlock { .. }))
+			{
+				let src = self.src.clone();
+				self.report(statement.span, EgerOlmadanDegilse { src, bad_bit: statement.span.into() });
+			}
+			self.statement(statement);
+			previous = Some(&statement.instr);
+
+			let keyword = match statement.instr {
+				InstructionEnum::Return(_) => "döndür",
+				InstructionEnum::Break => "durdur",
+				InstructionEnum::Continue => "devam et",
+				InstructionEnum::Throw(_) => "hata fırlat",
+				_ => continue,
+			};
+			// Everything after the first one is reported once, but still checked.
+			let rest = &statements[i + 1..];
+			if let (false, Some(first), Some(last)) = (unreachable, rest.first(), rest.last()) {
+				let span = first.span.to(last.extent());
+				let src = self.src.clone();
+				self.report(span, ErisilemeyenKod { src, bad_bit: span.into(), keyword: keyword.to_owned() });
+				unreachable = true;
+			}
+		}
+
+		// Tests run once the whole file has, so everything the file declares is visible in them.
+		for statement in statements.iter().filter(|x| matches!(x.instr, InstructionEnum::Test { .. })) {
+			self.body(Kind::Loop, &statement.body);
+		}
+	}
+
+	fn body(&mut self, kind: Kind, statements: &[Statement]) {
+		self.push(kind);
+		self.block(statements);
+		self.pop();
+	}
+
+	fn loop_body(&mut self, kind: Kind, statements: &[Statement]) {
+		self.loops += 1;
+		self.body(kind, statements);
+		self.loops -= 1;
+	}
+
+	fn statement(&mut self, statement: &Statement) {
+		let span = statement.span;
+		match &statement.instr {
+			InstructionEnum::Print(atoms) | InstructionEnum::Type(atoms) => {
+				for x in atoms {
+					self.atom(x, span);
+				}
+			}
+			InstructionEnum::Wait { amount, .. } => self.atom(amount, span),
+			InstructionEnum::Repeat { repeat_count, .. } => {
+				self.atom(repeat_count, span);
+				self.loop_body(Kind::Block, &statement.body);
+			}
+			InstructionEnum::For { from, to, step, name, .. } => {
+				self.atom(from, span);
+				self.atom(to, span);
+				if let Some(step) = step {
+					self.atom(step, span);
+				}
+				self.declare(name, span, false);
+				self.loop_body(Kind::Block, &statement.body);
+			}
+			InstructionEnum::ForIn { name, step, varname, .. } => {
+				self.read(name, span);
+				if let Some(step) = step {
+					self.atom(step, span);
+				}
+				self.loops += 1;
+				self.push(Kind::Loop);
+				self.declare(varname, span, false);
+				self.block(&statement.body);
+				self.pop();
+				self.loops -= 1;
+			}
+			InstructionEnum::WhileTrue { .. } => self.loop_body(Kind::Block, &statement.body),
+			// The condition is checked after the body, so it can use what the body declares.
+			InstructionEnum::RepeatUntil { condition, .. } => {
+				self.loop_body(Kind::Block, &statement.body);
+				self.atom(condition, span);
+			}
+			InstructionEnum::RepeatWhile { condition, .. }
+			| InstructionEnum::IfBlock { condition, .. }
+			| InstructionEnum::ElifBlock { condition, .. } => {
+				self.atom(condition, span);
+				match statement.instr {
+					InstructionEnum::RepeatWhile { .. } => self.loop_body(Kind::Block, &statement.body),
+					_ => self.body(Kind::Block, &statement.body),
+				}
+			}
+			InstructionEnum::ElseBlock { .. } | InstructionEnum::TryBlock { .. } | InstructionEnum::FinallyBlock { .. } => {
+				self.body(Kind::Block, &statement.body)
+			}
+			InstructionEnum::CatchBlock { name, .. } => {
+				if let Some(name) = name {
+					self.declare(name, span, false);
+				}
+				self.body(Kind::Block, &statement.body);
+			}
+			InstructionEnum::Function { name, args, .. } => {
+				// Default values are evaluated where the function is declared.
+				for default in args.iter().filter_map(|x| x.default_value.as_ref()) {
+					self.expression(default, span);
+				}
+				if let Some(signature) = self.scopes.last_mut().and_then(|x| x.functions.get_mut(name)) {
+					signature.defined = true;
+				}
+				let loops = std::mem::take(&mut self.loops);
+				self.functions += 1;
+				self.push(Kind::Function);
+				for arg in args {
+					self.declare(&arg.name, span, false);
+				}
+				self.block(&statement.body);
+				self.pop();
+				self.functions -= 1;
+				self.loops = loops;
+			}
+			InstructionEnum::CallFunction { name, args } => {
+				for x in args {
+					self.atom(x, span);
+				}
+				self.call(name, args.len(), span);
+			}
+			InstructionEnum::VariableDeclaration(name, value, method) => {
+				self.atom(value, span);
+				match method {
+					AssignmentMethod::Set => self.assign(name, span),
+					_ => self.read(name, span),
+				}
+			}
+			InstructionEnum::IndexAssignment { name, path, value, .. } => {
+				self.atom(value, span);
+				for accessor in path {
+					self.accessor(accessor, span);
+				}
+				self.read(name, span);
+			}
+			InstructionEnum::Import { namespace, .. } => {
+				self.scopes.last_mut().expect("blocks run inside a scope").modules.insert(namespace.clone());
+			}
+			InstructionEnum::Throw(value) | InstructionEnum::Assert(value) => self.atom(value, span),
+			InstructionEnum::AssertEqual(left, right) => {
+				self.atom(left, span);
+				self.atom(right, span);
+			}
+			// Checked at the end of the block it is in, see `block`.
+			InstructionEnum::Test { .. } => {}
+			InstructionEnum::Return(value) => {
+				self.atom(value, span);
+				if self.functions == 0 {
+					let src = self.src.clone();
+					self.report(span, FonksiyonDisindaDondur { src, bad_bit: span.into() });
+				}
+			}
+			InstructionEnum::Break | InstructionEnum::Continue => {
+				if self.loops == 0 {
+					let src = self.src.clone();
+					let keyword = if statement.instr == InstructionEnum::Break { "durdur" } else { "devam et" };
+					self.report(span, DonguDisinda { src, bad_bit: span.into(), keyword: keyword.to_owned() });
+				}
+			}
+			// Only `BuildScopes` produces these.
+			InstructionEnum::NoOp | InstructionEnum::Condition(_) | InstructionEnum::TryCatch(_) => {}
+		}
+	}
+
+	/// `span` is where the value is reported when it has no span of its own.
+	fn atom(&mut self, atom: &Atom, span: Span) {
+		let span = atom.span().unwrap_or(span);
+		match atom {
+			Atom::Expression(expr) => self.expression(expr, span),
+			Atom::YieldInstruction(instr) => match instr {
+				YieldInstructionEnum::Input { quote, .. } => self.expression(quote, span),
+				YieldInstructionEnum::Random { method, span: range } => {
+					if let RandomizerType::Boolean { chance } = method {
+						self.expression(chance, span);
+					}
+					if let Some((from, to)) = range {
+						self.expression(from, span);
+						self.expression(to, span);
+					}
+				}
+				YieldInstructionEnum::RandomVar(name) => self.read(name, span),
+				YieldInstructionEnum::CallFunction { name, args } => {
+					for x in args {
+						self.expression(x, span);
+					}
+					self.call(name, args.len(), span);
+				}
+				YieldInstructionEnum::Index(name, path) => {
+					self.read(name, span);
+					for accessor in path {
+						self.accessor(accessor, span);
+					}
+				}
+			},
+		}
+	}
+
+	fn accessor(&mut self, accessor: &Accessor, span: Span) {
+		match accessor {
+			Accessor::Index(index) => self.expression(index, span),
+			Accessor::Slice(from, to) => {
+				for x in [from, to].into_iter().flatten() {
+					self.expression(x, span);
+				}
+			}
+		}
+	}
+
+	fn expression(&mut self, expr: &Expression, span: Span) {
+		match expr {
+			Expression::Value(obj) => self.object(obj, span),
+			Expression::Spanned(inner, span) => self.expression(inner, *span),
+			Expression::Not(inner) => self.expression(inner, span),
+			Expression::Add(lhs, rhs)
+			| Expression::Sub(lhs, rhs)
+			| Expression::Mul(lhs, rhs)
+			| Expression::Div(lhs, rhs)
+			| Expression::Mod(lhs, rhs)
+			| Expression::Pow(lhs, rhs)
+			| Expression::LessThan(lhs, rhs)
+			| Expression::GreaterThan(lhs, rhs)
+			| Expression::LessThanOrEqual(lhs, rhs)
+			| Expression::GreaterThanOrEqual(lhs, rhs)
+			| Expression::Equal(lhs, rhs)
+			| Expression::NotEqual(lhs, rhs)
+			| Expression::In(lhs, rhs)
+			| Expression::And(lhs, rhs)
+			| Expression::Or(lhs, rhs) => {
+				self.expression(lhs, span);
+				self.expression(rhs, span);
+			}
+			Expression::Map(entries) => {
+				for (key, value) in entries {
+					self.expression(key, span);
+					self.expression(value, span);
+				}
+			}
+			Expression::Format(parts) => {
+				for part in parts {
+					self.expression(part, span);
+				}
+			}
+		}
+	}
+
+	fn object(&mut self, obj: &Object, span: Span) {
+		match obj {
+			Object::Variable(name) => self.read(name, span),
+			Object::Array(array) => {
+				for item in &array.value {
+					self.object(item, span);
+				}
+			}
+			Object::Map(map) => {
+				for (key, value) in &map.value {
+					self.object(key, span);
+					self.object(value, span);
+				}
+			}
+			_ => {}
+		}
+	}
+}
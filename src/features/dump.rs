@@ -0,0 +1,262 @@
+//! `zen dump`: the token stream and the parsed tree in a machine-readable form. The JSON layout is versioned, every
+//! node is an object with a `kind` and, where the parser recorded one, a `span`.
+
+use serde_json::{Value, json};
+
+use crate::{
+	features::{
+		ast,
+		tokenizer::{Accessor, Atom, InstructionEnum, Span, Statement, TokenData, YieldInstructionEnum},
+	},
+	library::Types::{Object, ParameterData, RandomizerType},
+	parsers::Parsers::Expression,
+	util::process::{ParseTokens, TokenizeSource},
+};
+
+/// Bumped whenever the JSON layout changes in a way that can break readers.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Çıktı biçimi
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum DumpFormat {
+	/// Makinenin okuyabileceği JSON
+	Json,
+	/// İnsanın okuyabileceği metin
+	Text,
+}
+
+/// Prints the tokens and / or the tree of `source`. Layout and syntax errors are returned like `zen run` does.
+pub fn dump(source: &str, filename: &str, tokens: bool, tree: bool, format: DumpFormat) -> miette::Result<()> {
+	let token_stream = TokenizeSource(source, filename, 0, true)?;
+	let statements = if tree { Some(ParseTokens(source, filename, token_stream.clone())?) } else { None };
+
+	match format {
+		DumpFormat::Json => {
+			let dumper = Dumper::new(source);
+			let mut out = json!({ "version": FORMAT_VERSION, "file": filename });
+			if tokens {
+				out["tokens"] = token_stream.iter().map(|x| dumper.token(x)).collect();
+			}
+			if let Some(statements) = &statements {
+				out["ast"] = dumper.statements(statements);
+			}
+			println!("{}", serde_json::to_string_pretty(&out).expect("a JSON value always serializes"));
+		}
+		DumpFormat::Text => {
+			if tokens {
+				let dumper = Dumper::new(source);
+				for token in &token_stream {
+					let (line, column) = dumper.position(token.span.start);
+					println!("{line}:{column}\t{:?}\t{:?}", token.token, token.slice);
+				}
+			}
+			if let Some(statements) = &statements {
+				print!("{}", ast::render(statements));
+			}
+		}
+	}
+	Ok(())
+}
+
+struct Dumper<'a> {
+	source: &'a str,
+	line_starts: Vec<usize>,
+}
+
+impl<'a> Dumper<'a> {
+	fn new(source: &'a str) -> Self {
+		let line_starts = std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1)).collect();
+		Self { source, line_starts }
+	}
+
+	/// 1-based line and column (in characters) of a byte offset.
+	fn position(&self, offset: usize) -> (usize, usize) {
+		let line = self.line_starts.partition_point(|x| *x <= offset);
+		let column = self.source[self.line_starts[line - 1]..offset].chars().count() + 1;
+		(line, column)
+	}
+
+	fn span(&self, span: Span) -> Value {
+		let (line, column) = self.position(span.start);
+		json!({ "start": span.start, "end": span.end, "line": line, "column": column })
+	}
+
+	fn token(&self, token: &TokenData) -> Value {
+		json!({ "kind": format!("{:?}", token.token), "slice": token.slice, "span": self.span(token.span) })
+	}
+
+	fn statements(&self, statements: &[Statement]) -> Value {
+		statements.iter().map(|x| self.statement(x)).collect()
+	}
+
+	fn statement(&self, statement: &Statement) -> Value {
+		let atoms = |atoms: &[Atom]| atoms.iter().map(|x| self.atom(x)).collect::<Value>();
+		let optional = |atom: &Option<Atom>| atom.as_ref().map_or(Value::Null, |x| self.atom(x));
+		let mut node = match &statement.instr {
+			InstructionEnum::Print(values) => json!({ "kind": "Print", "values": atoms(values) }),
+			InstructionEnum::Type(values) => json!({ "kind": "Type", "values": atoms(values) }),
+			InstructionEnum::Wait { amount, unit } => json!({ "kind": "Wait", "amount": self.atom(amount), "unit": format!("{:?}", unit) }),
+			InstructionEnum::Repeat { repeat_count, .. } => json!({ "kind": "Repeat", "count": self.atom(repeat_count) }),
+			InstructionEnum::For { from, to, step, name, .. } => json!({
+				"kind": "For",
+				"variable": name,
+				"from": self.atom(from),
+				"to": self.atom(to),
+				"step": optional(step),
+			}),
+			InstructionEnum::ForIn { name, step, varname, .. } => json!({
+				"kind": "ForIn",
+				"variable": varname,
+				"iterable": name,
+				"step": optional(step),
+			}),
+			InstructionEnum::RepeatUntil { condition, .. } => json!({ "kind": "RepeatUntil", "condition": self.atom(condition) }),
+			InstructionEnum::RepeatWhile { condition, .. } => json!({ "kind": "RepeatWhile", "condition": self.atom(condition) }),
+			InstructionEnum::IfBlock { condition, .. } => json!({ "kind": "If", "condition": self.atom(condition) }),
+			InstructionEnum::ElifBlock { condition, .. } => json!({ "kind": "Elif", "condition": self.atom(condition) }),
+			InstructionEnum::ElseBlock { .. } => json!({ "kind": "Else" }),
+			InstructionEnum::WhileTrue { .. } => json!({ "kind": "WhileTrue" }),
+			InstructionEnum::TryBlock { .. } => json!({ "kind": "Try" }),
+			InstructionEnum::CatchBlock { name, .. } => json!({ "kind": "Catch", "name": name }),
+			InstructionEnum::FinallyBlock { .. } => json!({ "kind": "Finally" }),
+			InstructionEnum::Function { name, args, .. } => json!({
+				"kind": "Function",
+				"name": name,
+				"parameters": args.iter().map(|x| self.parameter(x)).collect::<Value>(),
+			}),
+			InstructionEnum::CallFunction { name, args } => json!({ "kind": "CallFunction", "name": name, "arguments": atoms(args) }),
+			InstructionEnum::VariableDeclaration(name, value, method) => json!({
+				"kind": "VariableDeclaration",
+				"name": name,
+				"operator": ast::assignment(method),
+				"value": self.atom(value),
+			}),
+			InstructionEnum::IndexAssignment { name, path, value, method } => json!({
+				"kind": "IndexAssignment",
+				"name": name,
+				"path": path.iter().map(|x| self.accessor(x)).collect::<Value>(),
+				"operator": ast::assignment(method),
+				"value": self.atom(value),
+			}),
+			InstructionEnum::Import { path, namespace } => json!({ "kind": "Import", "path": path, "namespace": namespace }),
+			InstructionEnum::Throw(value) => json!({ "kind": "Throw", "value": self.atom(value) }),
+			InstructionEnum::Test { name, .. } => json!({ "kind": "Test", "name": name }),
+			InstructionEnum::Assert(value) => json!({ "kind": "Assert", "value": self.atom(value) }),
+			InstructionEnum::AssertEqual(left, right) => json!({ "kind": "AssertEqual", "left": self.atom(left), "right": self.atom(right) }),
+			InstructionEnum::Return(value) => json!({ "kind": "Return", "value": self.atom(value) }),
+			InstructionEnum::Break => json!({ "kind": "Break" }),
+			InstructionEnum::Continue => json!({ "kind": "Continue" }),
+			// Only built from the tree by `BuildScopes`, the parser never produces these.
+			InstructionEnum::NoOp | InstructionEnum::Condition(_) | InstructionEnum::TryCatch(_) => json!({ "kind": "NoOp" }),
+		};
+		node["span"] = self.span(statement.span);
+		node["body"] = self.statements(&statement.body);
+		node
+	}
+
+	fn atom(&self, atom: &Atom) -> Value {
+		let instr = match atom {
+			Atom::Expression(expr) => return self.expression(expr),
+			Atom::YieldInstruction(instr) => instr,
+		};
+		let expressions = |args: &[Expression]| args.iter().map(|x| self.expression(x)).collect::<Value>();
+		match instr {
+			YieldInstructionEnum::Input { quote, _type } => json!({
+				"kind": "Input",
+				"prompt": self.expression(quote),
+				"type": _type.as_ref().map(|x| format!("{:?}", x.token)),
+			}),
+			YieldInstructionEnum::Random { method, span } => {
+				let (method, chance) = match method {
+					RandomizerType::Number => ("Number", Value::Null),
+					RandomizerType::Letter => ("Letter", Value::Null),
+					RandomizerType::Boolean { chance } => ("Boolean", self.expression(chance)),
+				};
+				let range = span.as_ref().map_or(Value::Null, |(from, to)| json!([self.expression(from), self.expression(to)]));
+				json!({ "kind": "Random", "method": method, "chance": chance, "range": range })
+			}
+			YieldInstructionEnum::RandomVar(name) => json!({ "kind": "RandomVar", "name": name }),
+			YieldInstructionEnum::CallFunction { name, args } => json!({ "kind": "Call", "name": name, "arguments": expressions(args) }),
+			YieldInstructionEnum::Index(name, path) => json!({
+				"kind": "Index",
+				"name": name,
+				"path": path.iter().map(|x| self.accessor(x)).collect::<Value>(),
+			}),
+		}
+	}
+
+	fn expression(&self, expr: &Expression) -> Value {
+		let binary = |kind: &str, lhs: &Expression, rhs: &Expression| json!({ "kind": kind, "lhs": self.expression(lhs), "rhs": self.expression(rhs) });
+		match expr {
+			// The span becomes a field of the node it wraps instead of a node of its own.
+			Expression::Spanned(inner, span) => {
+				let mut node = self.expression(inner);
+				if node.get("span").is_none() {
+					node["span"] = self.span(*span);
+				}
+				node
+			}
+			Expression::Value(obj) => object(obj),
+			Expression::Not(inner) => json!({ "kind": "Not", "operand": self.expression(inner) }),
+			Expression::Add(lhs, rhs) => binary("Add", lhs, rhs),
+			Expression::Sub(lhs, rhs) => binary("Sub", lhs, rhs),
+			Expression::Mul(lhs, rhs) => binary("Mul", lhs, rhs),
+			Expression::Div(lhs, rhs) => binary("Div", lhs, rhs),
+			Expression::Mod(lhs, rhs) => binary("Mod", lhs, rhs),
+			Expression::Pow(lhs, rhs) => binary("Pow", lhs, rhs),
+			Expression::LessThan(lhs, rhs) => binary("LessThan", lhs, rhs),
+			Expression::GreaterThan(lhs, rhs) => binary("GreaterThan", lhs, rhs),
+			Expression::LessThanOrEqual(lhs, rhs) => binary("LessThanOrEqual", lhs, rhs),
+			Expression::GreaterThanOrEqual(lhs, rhs) => binary("GreaterThanOrEqual", lhs, rhs),
+			Expression::Equal(lhs, rhs) => binary("Equal", lhs, rhs),
+			Expression::NotEqual(lhs, rhs) => binary("NotEqual", lhs, rhs),
+			Expression::In(lhs, rhs) => binary("In", lhs, rhs),
+			Expression::And(lhs, rhs) => binary("And", lhs, rhs),
+			Expression::Or(lhs, rhs) => binary("Or", lhs, rhs),
+			Expression::Map(entries) => json!({
+				"kind": "Map",
+				"entries": entries
+					.iter()
+					.map(|(key, value)| json!({ "key": self.expression(key), "value": self.expression(value) }))
+					.collect::<Value>(),
+			}),
+			Expression::Format(parts) => json!({ "kind": "Format", "parts": parts.iter().map(|x| self.expression(x)).collect::<Value>() }),
+		}
+	}
+
+	fn accessor(&self, accessor: &Accessor) -> Value {
+		let bound = |x: &Option<Expression>| x.as_ref().map_or(Value::Null, |x| self.expression(x));
+		match accessor {
+			Accessor::Index(index) => json!({ "kind": "Index", "index": self.expression(index) }),
+			Accessor::Slice(from, to) => json!({ "kind": "Slice", "from": bound(from), "to": bound(to) }),
+		}
+	}
+
+	fn parameter(&self, parameter: &ParameterData) -> Value {
+		json!({
+			"name": parameter.name,
+			"type": parameter.data_type.as_ref().map(|x| format!("{:?}", x.token)),
+			"default": parameter.default_value.as_ref().map_or(Value::Null, |x| self.expression(x)),
+		})
+	}
+}
+
+/// A runtime value in the same layout as the literals of the tree.
+pub fn object(obj: &Object) -> Value {
+	match obj {
+		Object::Number(number) => json!({ "kind": "Number", "value": number.value }),
+		Object::Text(text) => json!({ "kind": "Text", "value": text.value }),
+		Object::Bool(boolean) => json!({ "kind": "Boolean", "value": boolean.value }),
+		Object::Variable(name) => json!({ "kind": "Variable", "name": name }),
+		Object::Array(array) => json!({ "kind": "Array", "items": array.value.iter().map(object).collect::<Value>() }),
+		Object::Map(map) => json!({
+			"kind": "Map",
+			"entries": map
+				.value
+				.iter()
+				.map(|(key, value)| json!({ "key": object(key), "value": object(value) }))
+				.collect::<Value>(),
+		}),
+		Object::Null => json!({ "kind": "Null" }),
+	}
+}
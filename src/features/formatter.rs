@@ -0,0 +1,279 @@
+//! `zen fmt`: writes a file back in one canonical layout. Every statement gets its own line indented with one tab
+//! per block, operators are spaced the same way everywhere, multi-word keywords and booleans are spelled one way and
+//! comments stay where they were. Text literals are copied as written.
+
+use std::path::{Path, PathBuf};
+
+use miette::{IntoDiagnostic, miette};
+
+use crate::{
+	features::{
+		ast,
+		tokenizer::{Statement, TokenData, TokenTable, tokenize_at},
+	},
+	util::process::{ParseTokens, TokenizeSource},
+};
+
+/// Formats `source`. Files that do not parse are returned as an error, nothing is guessed for them.
+pub fn format(source: &str, filename: &str) -> miette::Result<String> {
+	let tokens = TokenizeSource(source, filename, 0, true)?;
+	let statements = ParseTokens(source, filename, tokens.clone())?;
+
+	let mut out = Formatter { out: String::new(), last_line: None };
+	let comments = comments(source);
+	let mut comments = comments.iter().peekable();
+	let lines = lines(&tokens);
+
+	for (i, line) in lines.iter().enumerate() {
+		let (start, end) = (line.tokens[0].span.start, line.tokens.last().unwrap().span.end);
+		let previous = if i > 0 { lines[i - 1].depth } else { 0 };
+		// Comments before the line, or inside a line that spans several rows, go on their own row above it.
+		while let Some(comment) = comments.next_if(|x| x.start < end) {
+			out.comment(comment, previous, line.depth, line_of(source, start.min(comment.start)));
+		}
+		let mut row = render(source, &line.tokens);
+		if let Some(comment) = comments.next_if(|x| !source[end..x.start].contains('\n')) {
+			row.push(' ');
+			row.push_str(comment.text);
+		}
+		out.row(line.depth, &row, line_of(source, start), line_of(source, end));
+	}
+	let previous = lines.last().map_or(0, |x| x.depth);
+	for comment in comments {
+		out.comment(comment, previous, 0, comment.line);
+	}
+
+	let formatted = out.out;
+	// The layout may change, the program may not.
+	let reparsed = TokenizeSource(&formatted, filename, 0, true).and_then(|tokens| ParseTokens(&formatted, filename, tokens));
+	match reparsed {
+		Ok(reparsed) if shape(&reparsed) == shape(&statements) => Ok(formatted),
+		_ => Err(miette!("{filename} biçimlendirilirken programın anlamı değişti, dosyaya dokunulmadı.")),
+	}
+}
+
+/// `.zen` files under `paths`, directories are searched recursively.
+pub fn collect_files(paths: &[String]) -> miette::Result<Vec<PathBuf>> {
+	fn walk(path: &Path, out: &mut Vec<PathBuf>) -> miette::Result<()> {
+		if !path.is_dir() {
+			out.push(path.to_owned());
+			return Ok(());
+		}
+		let mut entries = std::fs::read_dir(path)
+			.into_diagnostic()?
+			.map(|x| x.map(|x| x.path()))
+			.collect::<Result<Vec<_>, _>>()
+			.into_diagnostic()?;
+		entries.sort();
+		for entry in entries {
+			if entry.is_dir() || entry.extension().is_some_and(|x| x == "zen") {
+				walk(&entry, out)?;
+			}
+		}
+		Ok(())
+	}
+
+	let mut out = Vec::new();
+	for path in paths {
+		walk(Path::new(path), &mut out)?;
+	}
+	Ok(out)
+}
+
+/// The tree without line numbers, two programs with the same shape only differ in layout.
+fn shape(statements: &[Statement]) -> String {
+	ast::render(statements)
+		.lines()
+		.map(|x| x.rsplit_once("  (satır ").map_or(x, |(row, _)| row))
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+struct Comment<'a> {
+	start: usize,
+	text: &'a str,
+	/// Tabs in front of the comment when it sits on a row of its own.
+	tabs: usize,
+	line: usize,
+}
+
+/// The `//` and `#` comments of `source`. Tokens inside interpolated texts are skipped, the text is copied whole.
+fn comments(source: &str) -> Vec<Comment<'_>> {
+	let mut interpolation = 0usize;
+	let mut out = Vec::new();
+	for token in tokenize_at(source, 0, 0, 1) {
+		match token.token {
+			TokenTable::InterpolationStart => interpolation += 1,
+			TokenTable::InterpolationEnd => interpolation -= 1,
+			TokenTable::Comment if interpolation == 0 => {
+				let row_start = source[..token.span.start].rfind('\n').map_or(0, |x| x + 1);
+				let leading = &source[row_start..token.span.start];
+				out.push(Comment {
+					start: token.span.start,
+					text: source[token.span.start..token.span.end].trim_end(),
+					tabs: if leading.trim().is_empty() { leading.matches('\t').count() } else { 0 },
+					line: line_of(source, token.span.start),
+				});
+			}
+			_ => {}
+		}
+	}
+	out
+}
+
+fn line_of(source: &str, offset: usize) -> usize {
+	source[..offset].matches('\n').count() + 1
+}
+
+struct Line<'a> {
+	depth: usize,
+	tokens: Vec<&'a TokenData>,
+}
+
+/// Splits the output of `tokenize_file` into logical lines with the block depth they are on.
+fn lines(tokens: &[TokenData]) -> Vec<Line<'_>> {
+	let mut out = Vec::new();
+	let mut depth = 0;
+	let mut current = Vec::new();
+	for token in tokens {
+		match token.token {
+			TokenTable::Indent => depth += 1,
+			TokenTable::Dedent => depth -= 1,
+			TokenTable::Newline => out.push(Line { depth, tokens: std::mem::take(&mut current) }),
+			_ => current.push(token),
+		}
+	}
+	out
+}
+
+struct Formatter {
+	out: String,
+	/// Source line the last written row ended on.
+	last_line: Option<usize>,
+}
+
+impl Formatter {
+	/// Writes a row that was on source lines `first..=last`. Blank lines between rows are kept, but never more
+	/// than one in a row.
+	fn row(&mut self, depth: usize, text: &str, first: usize, last: usize) {
+		if self.last_line.is_some_and(|x| first > x + 1) {
+			self.out.push('\n');
+		}
+		self.out.push_str(&"\t".repeat(depth));
+		self.out.push_str(text);
+		self.out.push('\n');
+		self.last_line = Some(last);
+	}
+
+	/// A comment on its own row keeps its indentation as long as it stays between the lines around it. `first` is
+	/// where the line it was taken out of starts, if it was inside one.
+	fn comment(&mut self, comment: &Comment, previous: usize, next: usize, first: usize) {
+		let depth = comment.tabs.clamp(next, previous.max(next));
+		self.row(depth, comment.text, first, comment.line);
+	}
+}
+
+/// Writes one logical line with canonical spelling and spacing.
+fn render(source: &str, tokens: &[&TokenData]) -> String {
+	let mut out = String::new();
+	// Whether each open `{` indexes a value (`a{1:3}`) rather than starting a map.
+	let mut brackets: Vec<bool> = Vec::new();
+	let mut previous: Option<&TokenData> = None;
+	// Set after a prefix operator such as the `-` of `-1`, it sticks to what follows.
+	let mut prefix = false;
+	let mut i = 0;
+
+	while i < tokens.len() {
+		let token = tokens[i];
+		let (text, next) = match token.token {
+			// The whole interpolated text is copied, with the tokens of its `{ifade}` parts.
+			TokenTable::InterpolationStart => {
+				let mut depth = 0;
+				let mut j = i;
+				loop {
+					match tokens[j].token {
+						TokenTable::InterpolationStart => depth += 1,
+						TokenTable::InterpolationEnd => depth -= 1,
+						_ => {}
+					}
+					if depth == 0 {
+						break;
+					}
+					j += 1;
+				}
+				(&source[token.span.start..tokens[j].span.end], j + 1)
+			}
+			TokenTable::StringLiteral => (&source[token.span.start..token.span.end], i + 1),
+			_ => (spelling(token), i + 1),
+		};
+
+		if let Some(previous) = previous {
+			let in_index = brackets.last() == Some(&true);
+			let tight = prefix
+				|| is_opening(&previous.token)
+				|| is_closing(&token.token)
+				|| matches!(token.token, TokenTable::Comma | TokenTable::Colon | TokenTable::Dot)
+				|| previous.token == TokenTable::Dot
+				|| (previous.token == TokenTable::Colon && in_index)
+				|| (token.token == TokenTable::LPAREN
+					&& (ends_operand(&previous.token) || matches!(previous.token, TokenTable::KeywordGirdi | TokenTable::KeywordIhtimal)))
+				|| (token.token == TokenTable::LCRBRACKET && ends_operand(&previous.token));
+			if !tight {
+				out.push(' ');
+			}
+		}
+
+		prefix = match token.token {
+			TokenTable::ExclamationMark => true,
+			TokenTable::MathOperatorSubtract | TokenTable::MathOperatorMod => !previous.is_some_and(|x| ends_operand(&x.token)),
+			_ => false,
+		};
+		match token.token {
+			TokenTable::LCRBRACKET => brackets.push(previous.is_some_and(|x| ends_operand(&x.token))),
+			TokenTable::LPAREN | TokenTable::LSQBRACKET => brackets.push(false),
+			TokenTable::RPAREN | TokenTable::RSQBRACKET | TokenTable::RCRBRACKET => {
+				brackets.pop();
+			}
+			_ => {}
+		}
+
+		out.push_str(text);
+		previous = Some(tokens[next - 1]);
+		i = next;
+	}
+	out
+}
+
+/// The one spelling `zen fmt` writes for tokens that can be written several ways.
+fn spelling(token: &TokenData) -> &str {
+	match token.token {
+		TokenTable::BooleanLiteral => match token.slice.as_str() {
+			"true" | "doğru" | "evet" | "yes" => "doğru",
+			_ => "yanlış",
+		},
+		_ => token.token.keyword().unwrap_or(&token.slice),
+	}
+}
+
+/// Tokens a value can end with, an operator after them is binary and a bracket indexes or calls them.
+fn ends_operand(token: &TokenTable) -> bool {
+	matches!(
+		token,
+		TokenTable::Identifier
+			| TokenTable::NumberLiteral
+			| TokenTable::StringLiteral
+			| TokenTable::BooleanLiteral
+			| TokenTable::InterpolationEnd
+			| TokenTable::RPAREN
+			| TokenTable::RSQBRACKET
+			| TokenTable::RCRBRACKET
+	)
+}
+
+fn is_opening(token: &TokenTable) -> bool {
+	matches!(token, TokenTable::LPAREN | TokenTable::LSQBRACKET | TokenTable::LCRBRACKET)
+}
+
+fn is_closing(token: &TokenTable) -> bool {
+	matches!(token, TokenTable::RPAREN | TokenTable::RSQBRACKET | TokenTable::RCRBRACKET)
+}
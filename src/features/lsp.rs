@@ -0,0 +1,472 @@
+//! `zen lsp`: a language server spoken over stdin / stdout. Every change to a file runs the tokenizer, the parser
+//! and `zen check` again and their reports become diagnostics. Hover and go-to-definition work on the last version
+//! of the file that parsed.
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+	CompletionItem, CompletionItemKind, CompletionOptions, CompletionResponse, Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+	DidCloseTextDocumentParams, DidOpenTextDocumentParams, GotoDefinitionResponse, Hover, HoverContents, HoverProviderCapability, Location,
+	MarkupContent, MarkupKind, OneOf, Position, PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+	TextDocumentSyncKind, Uri,
+	notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics},
+	request::{Completion, GotoDefinition, HoverRequest, Request as _},
+};
+use miette::{IntoDiagnostic, Report, Severity};
+
+use crate::{
+	features::{
+		ast, checker,
+		tokenizer::{AssignmentMethod, Atom, InstructionEnum, Span, Statement, TokenData, TokenTable, YieldInstructionEnum, tokenize_at, tokenize_file},
+	},
+	library::Types::{Object, ObjectType, ParameterData},
+	parsers::Parsers::{self, Expression},
+	util::process::{LayoutErrorReport, ParseError},
+};
+
+/// Serves one editor until it shuts the server down.
+pub fn start() -> miette::Result<()> {
+	let (connection, io_threads) = Connection::stdio();
+	let capabilities = ServerCapabilities {
+		text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+		hover_provider: Some(HoverProviderCapability::Simple(true)),
+		definition_provider: Some(OneOf::Left(true)),
+		completion_provider: Some(CompletionOptions::default()),
+		..Default::default()
+	};
+	connection.initialize(serde_json::to_value(capabilities).into_diagnostic()?).into_diagnostic()?;
+
+	Server { connection: &connection, documents: HashMap::new() }.run()?;
+	drop(connection);
+	io_threads.join().into_diagnostic()?;
+	Ok(())
+}
+
+struct Server<'a> {
+	connection: &'a Connection,
+	documents: HashMap<Uri, Document>,
+}
+
+impl Server<'_> {
+	fn run(&mut self) -> miette::Result<()> {
+		let connection = self.connection;
+		for message in &connection.receiver {
+			match message {
+				Message::Request(request) => {
+					if connection.handle_shutdown(&request).into_diagnostic()? {
+						return Ok(());
+					}
+					let response = match request.method.as_str() {
+						HoverRequest::METHOD => self.respond::<HoverRequest>(request, |document, params| {
+							document.hover(params.text_document_position_params.position)
+						}),
+						GotoDefinition::METHOD => self.respond::<GotoDefinition>(request, |document, params| {
+							let uri = params.text_document_position_params.text_document.uri;
+							let range = document.definition(params.text_document_position_params.position)?;
+							Some(GotoDefinitionResponse::Scalar(Location { uri, range }))
+						}),
+						Completion::METHOD => self.respond::<Completion>(request, |document, _| Some(CompletionResponse::Array(document.completion()))),
+						_ => Response::new_err(request.id, ErrorCode::MethodNotFound as i32, format!("Desteklenmeyen istek: {}", request.method)),
+					};
+					connection.sender.send(response.into()).into_diagnostic()?;
+				}
+				Message::Notification(notification) => self.notify(notification)?,
+				Message::Response(_) => {}
+			}
+		}
+		Ok(())
+	}
+
+	/// Answers a request about an open document, requests for documents the editor never opened get `null`.
+	fn respond<R: lsp_types::request::Request>(&self, request: Request, handler: impl Fn(&Document, R::Params) -> R::Result) -> Response
+	where
+		R::Result: Default,
+	{
+		// Every request the server answers names its document the same way.
+		let uri = serde_json::from_value::<Uri>(request.params["textDocument"]["uri"].clone()).ok();
+		let params = match serde_json::from_value::<R::Params>(request.params) {
+			Ok(params) => params,
+			Err(e) => return Response::new_err(request.id, ErrorCode::InvalidParams as i32, e.to_string()),
+		};
+		match uri.and_then(|x| self.documents.get(&x)) {
+			Some(document) => Response::new_ok(request.id, handler(document, params)),
+			None => Response::new_ok(request.id, R::Result::default()),
+		}
+	}
+
+	fn notify(&mut self, notification: Notification) -> miette::Result<()> {
+		match notification.method.as_str() {
+			DidOpenTextDocument::METHOD => {
+				let params: DidOpenTextDocumentParams = serde_json::from_value(notification.params).into_diagnostic()?;
+				let document = params.text_document;
+				self.update(document.uri, document.text, document.version)
+			}
+			DidChangeTextDocument::METHOD => {
+				let params: DidChangeTextDocumentParams = serde_json::from_value(notification.params).into_diagnostic()?;
+				// The server asks for full syncs, so the last change holds the whole text.
+				match params.content_changes.into_iter().last() {
+					Some(change) => self.update(params.text_document.uri, change.text, params.text_document.version),
+					None => Ok(()),
+				}
+			}
+			DidCloseTextDocument::METHOD => {
+				let params: DidCloseTextDocumentParams = serde_json::from_value(notification.params).into_diagnostic()?;
+				self.documents.remove(&params.text_document.uri);
+				self.publish(params.text_document.uri, Vec::new(), None)
+			}
+			_ => Ok(()),
+		}
+	}
+
+	fn update(&mut self, uri: Uri, text: String, version: i32) -> miette::Result<()> {
+		let document = Document::new(uri.as_str(), text);
+		let diagnostics = document.diagnostics.clone();
+		self.documents.insert(uri.clone(), document);
+		self.publish(uri, diagnostics, Some(version))
+	}
+
+	fn publish(&self, uri: Uri, diagnostics: Vec<Diagnostic>, version: Option<i32>) -> miette::Result<()> {
+		let params = PublishDiagnosticsParams { uri, diagnostics, version };
+		let notification = Notification::new(PublishDiagnostics::METHOD.to_owned(), params);
+		self.connection.sender.send(notification.into()).into_diagnostic()
+	}
+}
+
+/// What the cursor can point at.
+enum Symbol<'a> {
+	Function(&'a Statement),
+	Parameter(&'a Statement, &'a ParameterData),
+	/// The statement that first gives the variable a value.
+	Variable(&'a Statement),
+}
+
+struct Document {
+	text: String,
+	line_starts: Vec<usize>,
+	/// Every token of the text, including the ones inside interpolated texts.
+	tokens: Vec<TokenData>,
+	/// Empty while the text does not parse.
+	statements: Vec<Statement>,
+	diagnostics: Vec<Diagnostic>,
+}
+
+impl Document {
+	fn new(filename: &str, text: String) -> Self {
+		let (statements, reports) = match tokenize_file(&text, 0, true) {
+			Err(e) => (Vec::new(), vec![LayoutErrorReport(filename, &text, e)]),
+			Ok(tokens) => match Parsers::parse_program(tokens.clone()) {
+				Ok(statements) => {
+					let reports = checker::lint(&statements, &text, filename);
+					(statements, reports)
+				}
+				Err(errors) => (Vec::new(), errors.iter().map(|e| ParseError(filename, &text, &tokens, e)).collect()),
+			},
+		};
+		let mut document = Document {
+			line_starts: std::iter::once(0).chain(text.match_indices('\n').map(|(i, _)| i + 1)).collect(),
+			tokens: tokenize_at(&text, 0, 0, 1),
+			text,
+			statements,
+			diagnostics: Vec::new(),
+		};
+		document.diagnostics = reports.iter().map(|x| document.diagnostic(x)).collect();
+		document
+	}
+
+	/// LSP positions count UTF-16 code units, spans count bytes.
+	fn position(&self, offset: usize) -> Position {
+		let line = self.line_starts.partition_point(|x| *x <= offset) - 1;
+		let character = self.text[self.line_starts[line]..offset].encode_utf16().count();
+		Position::new(line as u32, character as u32)
+	}
+
+	fn offset(&self, position: Position) -> Option<usize> {
+		let start = *self.line_starts.get(position.line as usize)?;
+		let end = self.line_starts.get(position.line as usize + 1).map_or(self.text.len(), |x| x - 1);
+		let mut units = 0;
+		for (i, c) in self.text[start..end].char_indices() {
+			if units >= position.character as usize {
+				return Some(start + i);
+			}
+			units += c.len_utf16();
+		}
+		Some(end)
+	}
+
+	fn range(&self, span: Span) -> Range {
+		Range::new(self.position(span.start), self.position(span.end))
+	}
+
+	fn diagnostic(&self, report: &Report) -> Diagnostic {
+		let range = report
+			.labels()
+			.and_then(|mut x| x.next())
+			.map_or_else(Range::default, |label| self.range(Span::new(0, label.offset()..label.offset() + label.len(), 0)));
+		let mut message = report.to_string();
+		if let Some(help) = report.help() {
+			message.push('\n');
+			message.push_str(&help.to_string());
+		}
+		Diagnostic {
+			range,
+			severity: Some(match report.severity() {
+				Some(Severity::Warning) => DiagnosticSeverity::WARNING,
+				Some(Severity::Advice) => DiagnosticSeverity::HINT,
+				_ => DiagnosticSeverity::ERROR,
+			}),
+			source: Some("zen".to_owned()),
+			message,
+			..Default::default()
+		}
+	}
+
+	/// Index of the token under the cursor, a word right before the cursor counts too.
+	fn token_at(&self, position: Position) -> Option<usize> {
+		let offset = self.offset(position)?;
+		let word = |x: &TokenData| {
+			matches!(
+				x.token,
+				TokenTable::Identifier | TokenTable::NumberLiteral | TokenTable::StringLiteral | TokenTable::BooleanLiteral
+			) || x.token.keyword().is_some()
+		};
+		self.tokens
+			.iter()
+			.enumerate()
+			.filter(|(_, x)| x.span.start <= offset && offset <= x.span.end && !matches!(x.token, TokenTable::Tab | TokenTable::Newline))
+			.max_by_key(|(_, x)| (word(x), x.span.start))
+			.map(|(i, _)| i)
+	}
+
+	/// The statements around `offset`, outermost first.
+	fn path(&self, offset: usize) -> Vec<&Statement> {
+		let mut path = Vec::new();
+		let mut block = &self.statements[..];
+		while let Some(statement) = block.iter().find(|x| x.extent().start <= offset && offset <= x.extent().end) {
+			path.push(statement);
+			block = &statement.body;
+		}
+		path
+	}
+
+	/// Resolves the identifier at `index` the way the interpreter would: functions are looked up from the innermost
+	/// block outwards, variables only up to the function they are used in.
+	fn resolve(&self, index: usize) -> Option<Symbol<'_>> {
+		let token = &self.tokens[index];
+		let name = token.slice.as_str();
+		let neighbour = |x: Option<&TokenData>| x.map(|x| x.token.clone());
+		let previous = neighbour(self.tokens[..index].iter().rev().find(|x| x.token != TokenTable::Tab));
+		let next = neighbour(self.tokens[index + 1..].iter().find(|x| x.token != TokenTable::Tab));
+		// Members of maps and modules are not declared anywhere in the file.
+		if previous == Some(TokenTable::Dot) || next == Some(TokenTable::Dot) && self.modules().contains(&name) {
+			return None;
+		}
+		let path = self.path(token.span.start);
+
+		if next == Some(TokenTable::LPAREN) || previous == Some(TokenTable::KeywordFonksiyon) {
+			let blocks = std::iter::once(&self.statements[..]).chain(path.iter().map(|x| &x.body[..])).collect::<Vec<_>>();
+			return blocks.into_iter().rev().find_map(|block| {
+				block.iter().find(|x| matches!(&x.instr, InstructionEnum::Function { name: x, .. } if x == name)).map(Symbol::Function)
+			});
+		}
+
+		let frame = path.iter().rposition(|x| matches!(x.instr, InstructionEnum::Function { .. }));
+		if let Some(function) = frame.map(|i| path[i])
+			&& let InstructionEnum::Function { args, .. } = &function.instr
+			&& let Some(param) = args.iter().find(|x| x.name == name)
+		{
+			return Some(Symbol::Parameter(function, param));
+		}
+		let body = frame.map_or(&self.statements[..], |i| &path[i].body[..]);
+		first_definition(body, name).map(Symbol::Variable)
+	}
+
+	fn modules(&self) -> Vec<&str> {
+		fn walk<'a>(statements: &'a [Statement], out: &mut Vec<&'a str>) {
+			for statement in statements {
+				if let InstructionEnum::Import { namespace, .. } = &statement.instr {
+					out.push(namespace);
+				}
+				walk(&statement.body, out);
+			}
+		}
+		let mut out = Vec::new();
+		walk(&self.statements, &mut out);
+		out
+	}
+
+	/// Where `name` is written in the header of `statement`. Loop variables and `yakala` names come last on the line,
+	/// parameters are followed by their `:`.
+	fn name_span(&self, statement: &Statement, name: &str, last: bool, parameter: bool) -> Span {
+		let header = self
+			.tokens
+			.iter()
+			.enumerate()
+			.filter(|(_, x)| statement.span.start <= x.span.start && x.span.end <= statement.span.end)
+			.filter(|(_, x)| x.token == TokenTable::Identifier && x.slice == name)
+			.filter(|(i, _)| !parameter || self.tokens.get(i + 1).is_some_and(|x| x.token == TokenTable::Colon))
+			.map(|(_, x)| x.span)
+			.collect::<Vec<_>>();
+		let found = if last { header.last() } else { header.first() };
+		found.copied().unwrap_or(statement.span)
+	}
+
+	fn symbol_span(&self, symbol: &Symbol, name: &str) -> Span {
+		match symbol {
+			Symbol::Function(statement) => self.name_span(statement, name, false, false),
+			Symbol::Parameter(statement, _) => self.name_span(statement, name, false, true),
+			Symbol::Variable(statement) => {
+				let last = !matches!(statement.instr, InstructionEnum::VariableDeclaration(..));
+				self.name_span(statement, name, last, false)
+			}
+		}
+	}
+
+	fn definition(&self, position: Position) -> Option<Range> {
+		let index = self.token_at(position)?;
+		let token = &self.tokens[index];
+		if token.token != TokenTable::Identifier {
+			return None;
+		}
+		let symbol = self.resolve(index)?;
+		Some(self.range(self.symbol_span(&symbol, &token.slice)))
+	}
+
+	fn hover(&self, position: Position) -> Option<Hover> {
+		let index = self.token_at(position)?;
+		let token = &self.tokens[index];
+		let kind = |kind: ObjectType| format!("Tip: **{kind}**");
+		let value = match token.token {
+			TokenTable::NumberLiteral | TokenTable::StringLiteral | TokenTable::BooleanLiteral => kind(token.asObject().get_type()),
+			TokenTable::InterpolationStart | TokenTable::InterpolationEnd => kind(ObjectType::Text),
+			TokenTable::KeywordSayı | TokenTable::KeywordMetin | TokenTable::KeywordMantıksal => kind(type_keyword(token)?),
+			TokenTable::Identifier => {
+				let symbol = self.resolve(index)?;
+				let line = self.position(self.symbol_span(&symbol, &token.slice).start).line + 1;
+				match symbol {
+					Symbol::Function(statement) => format!("```zen\nfonksiyon {}\n```\nSatır {line}", signature(statement)),
+					Symbol::Parameter(_, param) => {
+						let mut out = format!("```zen\n{}\n```\nParametre", ast::parameter(param));
+						if let Some(kind) = param.data_type.as_ref().and_then(type_keyword) {
+							out.push_str(&format!(", tip: **{kind}**"));
+						}
+						out
+					}
+					Symbol::Variable(statement) => {
+						let mut out = format!("```zen\n{}\n```\nDeğişken, satır {line}", token.slice);
+						if let Some(kind) = variable_type(statement) {
+							out.push_str(&format!(", tip: **{kind}**"));
+						}
+						out
+					}
+				}
+			}
+			_ => return None,
+		};
+		Some(Hover {
+			contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+			range: Some(self.range(token.span)),
+		})
+	}
+
+	/// Every keyword of `TokenTable`, then the functions and variables of the file.
+	fn completion(&self) -> Vec<CompletionItem> {
+		fn names(statements: &[Statement], out: &mut Vec<(String, CompletionItemKind)>) {
+			for statement in statements {
+				let name = match &statement.instr {
+					InstructionEnum::Function { name, .. } => Some((name, CompletionItemKind::FUNCTION)),
+					InstructionEnum::VariableDeclaration(name, _, AssignmentMethod::Set)
+					| InstructionEnum::For { name, .. }
+					| InstructionEnum::ForIn { varname: name, .. } => Some((name, CompletionItemKind::VARIABLE)),
+					_ => None,
+				};
+				if let Some((name, kind)) = name
+					&& !out.iter().any(|(x, _)| x == name)
+				{
+					out.push((name.clone(), kind));
+				}
+				names(&statement.body, out);
+			}
+		}
+
+		let mut items = TokenTable::KEYWORDS
+			.iter()
+			.filter_map(TokenTable::keyword)
+			.map(|x| (x.to_owned(), CompletionItemKind::KEYWORD))
+			.collect::<Vec<_>>();
+		names(&self.statements, &mut items);
+		items
+			.into_iter()
+			.map(|(label, kind)| CompletionItem { label, kind: Some(kind), ..Default::default() })
+			.collect()
+	}
+}
+
+/// The first statement in `statements` that gives `name` a value, without looking into nested functions.
+fn first_definition<'a>(statements: &'a [Statement], name: &str) -> Option<&'a Statement> {
+	for statement in statements {
+		let defined = match &statement.instr {
+			InstructionEnum::VariableDeclaration(x, _, AssignmentMethod::Set)
+			| InstructionEnum::For { name: x, .. }
+			| InstructionEnum::ForIn { varname: x, .. }
+			| InstructionEnum::CatchBlock { name: Some(x), .. } => x == name,
+			InstructionEnum::Function { .. } => continue,
+			_ => false,
+		};
+		if defined {
+			return Some(statement);
+		}
+		if let Some(found) = first_definition(&statement.body, name) {
+			return Some(found);
+		}
+	}
+	None
+}
+
+fn signature(statement: &Statement) -> String {
+	match &statement.instr {
+		InstructionEnum::Function { name, args, .. } => {
+			format!("{name}({})", args.iter().map(ast::parameter).collect::<Vec<_>>().join(", "))
+		}
+		_ => String::new(),
+	}
+}
+
+fn type_keyword(token: &TokenData) -> Option<ObjectType> {
+	match token.token {
+		TokenTable::KeywordSayı => Some(ObjectType::Number),
+		TokenTable::KeywordMetin => Some(ObjectType::Text),
+		TokenTable::KeywordMantıksal => Some(ObjectType::Boolean),
+		_ => None,
+	}
+}
+
+/// The type a variable gets where it is declared, when that can be told without running anything.
+fn variable_type(statement: &Statement) -> Option<ObjectType> {
+	fn expression(expr: &Expression) -> Option<ObjectType> {
+		match expr {
+			Expression::Spanned(inner, _) => expression(inner),
+			Expression::Value(obj) if !matches!(**obj, Object::Variable(_)) => Some(obj.get_type()),
+			Expression::Map(_) => Some(ObjectType::Map),
+			Expression::Format(_) => Some(ObjectType::Text),
+			Expression::Not(_)
+			| Expression::LessThan(..)
+			| Expression::GreaterThan(..)
+			| Expression::LessThanOrEqual(..)
+			| Expression::GreaterThanOrEqual(..)
+			| Expression::Equal(..)
+			| Expression::NotEqual(..)
+			| Expression::In(..) => Some(ObjectType::Boolean),
+			_ => None,
+		}
+	}
+	match &statement.instr {
+		InstructionEnum::For { .. } => Some(ObjectType::Number),
+		InstructionEnum::CatchBlock { .. } => Some(ObjectType::Map),
+		InstructionEnum::VariableDeclaration(_, Atom::Expression(expr), _) => expression(expr),
+		InstructionEnum::VariableDeclaration(_, Atom::YieldInstruction(YieldInstructionEnum::Input { _type, .. }), _) => {
+			_type.as_ref().map_or(Some(ObjectType::Text), type_keyword)
+		}
+		_ => None,
+	}
+}
@@ -1,2 +1,8 @@
+pub mod ast;
+pub mod checker;
+pub mod dump;
+pub mod formatter;
+pub mod lsp;
 pub mod preprocessor;
 pub mod tokenizer;
+pub mod trace;
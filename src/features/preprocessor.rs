@@ -1,9 +1,42 @@
 /// This is where we will remove comments, format the code, and do other preprocessing tasks
 use regex::Regex;
 
+use crate::features::tokenizer::{TokenData, TokenTable};
+
 pub fn index(buffer: &mut String) {
 	let re = Regex::new(r"#.*").unwrap();
 	let replaced = re.replace_all(buffer, "").to_string();
 	buffer.clear();
 	buffer.push_str(&replaced);
 }
+
+/// The source the way the parser sees it after `tokenize_file`: comments are gone, every logical line is one row
+/// (lines continued inside brackets are joined, `;` starts a new row) and each block level is one tab.
+pub fn layout(source: &str, tokens: &[TokenData]) -> String {
+	let mut out = String::new();
+	let mut depth = 0;
+	let mut previous: Option<&TokenData> = None;
+	for token in tokens {
+		match token.token {
+			TokenTable::Indent => depth += 1,
+			TokenTable::Dedent => depth -= 1,
+			TokenTable::Newline => {
+				out.push('\n');
+				previous = None;
+			}
+			_ => {
+				match previous {
+					None => out.push_str(&"\t".repeat(depth)),
+					// Spacing inside a line is kept as written, a gap that runs over lines becomes a single space.
+					Some(previous) => {
+						let gap = &source[previous.span.end..token.span.start];
+						out.push_str(if gap.contains('\n') { " " } else { gap });
+					}
+				}
+				out.push_str(&source[token.span.start..token.span.end]);
+				previous = Some(token);
+			}
+		}
+	}
+	out
+}
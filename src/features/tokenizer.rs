@@ -8,19 +8,26 @@ use std::{
 
 use crate::{
 	Input,
-	library::Types::{Object, ParameterData, RandomizerType, TimeUnit},
+	library::{
+		Error::{DegiskenBulunamadı, TipHatası},
+		Types::{LETTERARRAY, Object, ParameterData, RandomizerType, TimeUnit},
+	},
 	parsers::Parsers::Expression,
-	util::ScopeManager::{ConditionBlock, ScopeAction, ScopeManager},
+	util::ScopeManager::{ConditionBlock, ScopeAction, ScopeManager, TryStructure},
 };
 use logos::Logos;
+use miette::{NamedSource, SourceSpan};
 use rand::Rng;
 
 #[derive(Clone, Logos, Debug, PartialEq, PartialOrd, Hash, Eq)]
 pub enum TokenTable {
 	#[regex(r"\t")]
 	Tab,
+	#[token("\n")]
+	Newline,
 
-	#[token(r"//")]
+	/// `// ...` or `# ...` up to the end of the line.
+	#[regex(r"(?://|#)[^\n]*")]
 	Comment,
 
 	#[token("?")]
@@ -94,8 +101,22 @@ pub enum TokenTable {
 	KeywordSürekliTekrarla,
 	#[regex(r"(?:defa|kere|kez)[ \t]+tekrarla")]
 	KeywordNDefaTekrarla,
+	#[regex(r"olana[ \t]+kadar[ \t]+tekrarla")]
+	KeywordOlanaKadarTekrarla,
+	#[regex(r"olmadığı[ \t]+sürece[ \t]+tekrarla")]
+	KeywordOlmadığıSüreceTekrarla,
 	#[token(r"fonksiyon")]
 	KeywordFonksiyon,
+	#[regex(r"içe[ \t]+aktar")]
+	KeywordIçeAktar,
+	#[token("dene")]
+	KeywordDene,
+	#[token("yakala")]
+	KeywordYakala,
+	#[token("sonunda")]
+	KeywordSonunda,
+	#[regex(r"hata[ \t]+fırlat")]
+	KeywordHataFırlat,
 	#[regex(r"devam[ \t]+et")]
 	KeywordDevamEt,
 	#[token(r"durdur")]
@@ -106,6 +127,12 @@ pub enum TokenTable {
 	KeywordTip,
 	#[token("rastgele")]
 	KeywordRastgele,
+	#[token("test")]
+	KeywordTest,
+	#[token("doğrula")]
+	KeywordDoğrula,
+	#[token("eşit_olmalı")]
+	KeywordEşitOlmalı,
 
 	#[token("==")]
 	ComparisonOperatorEqual,
@@ -173,6 +200,7 @@ pub enum TokenTable {
 	#[regex("true|doğru|evet|yes|false|yanlış|hayır|no")]
 	BooleanLiteral,
 	#[regex(r#""[^"\\]*(?:\\.[^"\\]*)*""#)]
+	#[regex(r#""""(?:[^"\\]|\\.|"[^"\\]|""[^"\\])*""""#)]
 	StringLiteral,
 	#[regex(r"(0|[1-9][0-9]*)(\.[0-9]+)?")]
 	NumberLiteral,
@@ -181,7 +209,16 @@ pub enum TokenTable {
 	)]
 	Identifier,
 
-	#[regex(r"[ \n\r]+", logos::skip)]
+	/// Opening quote of a text literal that contains `{ifade}` parts, produced by `tokenize_at` rather than the lexer.
+	InterpolationStart,
+	/// Closing quote of an interpolated text literal.
+	InterpolationEnd,
+	/// A line indented one tab deeper than the one before it, produced by `tokenize_file`.
+	Indent,
+	/// Closes one `Indent` when a line goes back to a shallower level.
+	Dedent,
+
+	#[regex(r"[ \r]+", logos::skip)]
 	Error,
 }
 
@@ -189,6 +226,112 @@ impl TokenTable {
 	pub fn asTokenData(&self) -> TokenData {
 		TokenData::default(self.clone())
 	}
+
+	/// Every keyword of the language, in the order they are declared above.
+	pub const KEYWORDS: [TokenTable; 47] = [
+		TokenTable::KeywordEğer,
+		TokenTable::Keywordİse,
+		TokenTable::KeywordDeğilseVe,
+		TokenTable::KeywordDeğilse,
+		TokenTable::KeywordSayı,
+		TokenTable::KeywordMetin,
+		TokenTable::KeywordMantıksal,
+		TokenTable::KeywordIhtimal,
+		TokenTable::KeywordHarf,
+		TokenTable::KeywordSalise,
+		TokenTable::KeywordSaniye,
+		TokenTable::KeywordDakika,
+		TokenTable::KeywordSaat,
+		TokenTable::KeywordGün,
+		TokenTable::KeywordHafta,
+		TokenTable::KeywordAy,
+		TokenTable::KeywordYıl,
+		TokenTable::KeywordBekle,
+		TokenTable::KeywordVe,
+		TokenTable::KeywordVeya,
+		TokenTable::Keywordİle,
+		TokenTable::KeywordAralığında,
+		TokenTable::Keywordİçinde,
+		TokenTable::KeywordArasında,
+		TokenTable::KeywordArtarak,
+		TokenTable::KeywordDolan,
+		TokenTable::KeywordDeğil,
+		TokenTable::KeywordYazdır,
+		TokenTable::KeywordGirdi,
+		TokenTable::KeywordSürekliTekrarla,
+		TokenTable::KeywordNDefaTekrarla,
+		TokenTable::KeywordOlanaKadarTekrarla,
+		TokenTable::KeywordOlmadığıSüreceTekrarla,
+		TokenTable::KeywordFonksiyon,
+		TokenTable::KeywordIçeAktar,
+		TokenTable::KeywordDene,
+		TokenTable::KeywordYakala,
+		TokenTable::KeywordSonunda,
+		TokenTable::KeywordHataFırlat,
+		TokenTable::KeywordDevamEt,
+		TokenTable::KeywordDurdur,
+		TokenTable::KeywordDöndür,
+		TokenTable::KeywordTip,
+		TokenTable::KeywordRastgele,
+		TokenTable::KeywordTest,
+		TokenTable::KeywordDoğrula,
+		TokenTable::KeywordEşitOlmalı,
+	];
+
+	/// The canonical spelling of a keyword. Keywords that can be written several ways (`kez` / `defa` / `kere
+	/// tekrarla`) get one of them.
+	pub fn keyword(&self) -> Option<&'static str> {
+		Some(match self {
+			TokenTable::KeywordEğer => "eğer",
+			TokenTable::Keywordİse => "ise",
+			TokenTable::KeywordDeğilseVe => "değilse ve",
+			TokenTable::KeywordDeğilse => "değilse",
+			TokenTable::KeywordSayı => "sayı",
+			TokenTable::KeywordMetin => "metin",
+			TokenTable::KeywordMantıksal => "mantıksal",
+			TokenTable::KeywordIhtimal => "ihtimal",
+			TokenTable::KeywordHarf => "harf",
+			TokenTable::KeywordSalise => "salise",
+			TokenTable::KeywordSaniye => "saniye",
+			TokenTable::KeywordDakika => "dakika",
+			TokenTable::KeywordSaat => "saat",
+			TokenTable::KeywordGün => "gün",
+			TokenTable::KeywordHafta => "hafta",
+			TokenTable::KeywordAy => "ay",
+			TokenTable::KeywordYıl => "yıl",
+			TokenTable::KeywordBekle => "bekle",
+			TokenTable::KeywordVe => "ve",
+			TokenTable::KeywordVeya => "veya",
+			TokenTable::Keywordİle => "ile",
+			TokenTable::KeywordAralığında => "aralığında",
+			TokenTable::Keywordİçinde => "içinde",
+			TokenTable::KeywordArasında => "arasında",
+			TokenTable::KeywordArtarak => "artarak",
+			TokenTable::KeywordDolan => "dolan",
+			TokenTable::KeywordDeğil => "değil",
+			TokenTable::KeywordYazdır => "yazdır",
+			TokenTable::KeywordGirdi => "girdi",
+			TokenTable::KeywordSürekliTekrarla => "sürekli tekrarla",
+			TokenTable::KeywordNDefaTekrarla => "kez tekrarla",
+			TokenTable::KeywordOlanaKadarTekrarla => "olana kadar tekrarla",
+			TokenTable::KeywordOlmadığıSüreceTekrarla => "olmadığı sürece tekrarla",
+			TokenTable::KeywordFonksiyon => "fonksiyon",
+			TokenTable::KeywordIçeAktar => "içe aktar",
+			TokenTable::KeywordDene => "dene",
+			TokenTable::KeywordYakala => "yakala",
+			TokenTable::KeywordSonunda => "sonunda",
+			TokenTable::KeywordHataFırlat => "hata fırlat",
+			TokenTable::KeywordDevamEt => "devam et",
+			TokenTable::KeywordDurdur => "durdur",
+			TokenTable::KeywordDöndür => "döndür",
+			TokenTable::KeywordTip => "tip",
+			TokenTable::KeywordRastgele => "rastgele",
+			TokenTable::KeywordTest => "test",
+			TokenTable::KeywordDoğrula => "doğrula",
+			TokenTable::KeywordEşitOlmalı => "eşit_olmalı",
+			_ => return None,
+		})
+	}
 }
 
 impl Display for TokenTable {
@@ -202,11 +345,48 @@ pub struct TokenData {
 	pub isOk: bool,
 	pub token: TokenTable,
 	pub slice: String,
-	pub span: Range<usize>,
+	pub span: Span,
+}
+
+/// Where a piece of code lives: the file it was read from (an id registered in the `ScopeManager`),
+/// its byte range inside that whole file and the line it starts on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+	pub file: usize,
+	pub start: usize,
+	pub end: usize,
+	pub line: usize,
+}
+
+impl Span {
+	pub fn new(file: usize, range: Range<usize>, line: usize) -> Self {
+		Self {
+			file,
+			start: range.start,
+			end: range.end,
+			line,
+		}
+	}
+
+	/// Smallest span covering both `self` and `other`.
+	pub fn to(&self, other: Span) -> Span {
+		Span {
+			file: self.file,
+			start: self.start.min(other.start),
+			end: self.end.max(other.end),
+			line: self.line.min(other.line),
+		}
+	}
+}
+
+impl From<Span> for SourceSpan {
+	fn from(span: Span) -> Self {
+		SourceSpan::new(span.start.into(), span.end - span.start)
+	}
 }
 
 impl TokenData {
-	pub fn new(token: TokenTable, slice: String, span: Range<usize>) -> Self {
+	pub fn new(token: TokenTable, slice: String, span: Span) -> Self {
 		Self {
 			isOk: true,
 			token,
@@ -220,7 +400,7 @@ impl TokenData {
 			isOk: true,
 			token,
 			slice: "".to_owned(),
-			span: 0..0,
+			span: Span::default(),
 		}
 	}
 
@@ -238,6 +418,9 @@ impl TokenData {
 			TokenTable::ComparisonOperatorGreaterThanOrEqual => Expression::GreaterThanOrEqual,
 			TokenTable::ComparisonOperatorLessThan => Expression::LessThan,
 			TokenTable::ComparisonOperatorLessThanOrEqual => Expression::LessThanOrEqual,
+			TokenTable::Keywordİçinde => Expression::In,
+			TokenTable::KeywordVe => Expression::And,
+			TokenTable::KeywordVeya => Expression::Or,
 			_ => panic!(),
 		}
 	}
@@ -349,6 +532,48 @@ pub enum AssignmentMethod {
 	Div,
 }
 
+impl AssignmentMethod {
+	/// Combines the previous value with the assigned one. Compound methods fail if there is no previous value.
+	pub fn apply(&self, previous: Option<Object>, value: Object, src: NamedSource<String>, span: SourceSpan) -> miette::Result<Object> {
+		let previous = match (self, previous) {
+			(AssignmentMethod::Set, _) => return Ok(value),
+			(_, Some(previous)) => previous,
+			(_, None) => return Err(DegiskenBulunamadı { src, bad_bit: span })?,
+		};
+		Ok(self.combine(previous, value))
+	}
+
+	/// The operator behind the method, `Set` just keeps the new value.
+	pub fn combine(&self, previous: Object, value: Object) -> Object {
+		match self {
+			AssignmentMethod::Set => value,
+			AssignmentMethod::Add => previous + value,
+			AssignmentMethod::Sub => previous - value,
+			AssignmentMethod::Mul => previous * value,
+			AssignmentMethod::Div => previous / value,
+		}
+	}
+}
+
+/// A single `{...}` after a name: either an index / key (`a{0}`) or a slice (`a{1:3}`, `a{:2}`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Accessor<T = Expression> {
+	Index(T),
+	Slice(Option<T>, Option<T>),
+}
+
+impl Accessor {
+	pub fn evaluate(&self, currentScope: usize, manager: &mut ScopeManager) -> miette::Result<Accessor<Object>> {
+		Ok(match self {
+			Accessor::Index(index) => Accessor::Index(index.evaluate(currentScope, manager)?),
+			Accessor::Slice(from, to) => Accessor::Slice(
+				from.as_ref().map(|x| x.evaluate(currentScope, manager)).transpose()?,
+				to.as_ref().map(|x| x.evaluate(currentScope, manager)).transpose()?,
+			),
+		})
+	}
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConditionBlockType {
 	If,
@@ -394,17 +619,34 @@ impl Atom {
 		matches!(self, Atom::YieldInstruction(_))
 	}
 
-	pub fn resolve(&self, currentScope: usize, manager: &mut ScopeManager) -> Expression {
+	pub fn span(&self) -> Option<Span> {
 		match self {
+			Atom::Expression(expr) => expr.span(),
+			Atom::YieldInstruction(_) => None,
+		}
+	}
+
+	pub fn resolve(&self, currentScope: usize, manager: &mut ScopeManager) -> miette::Result<Expression> {
+		Ok(match self {
 			Atom::Expression(expr) => expr.clone(),
 			Atom::YieldInstruction(instr) => match instr {
 				YieldInstructionEnum::Input { quote, _type } => {
-					let out = Object::from(Input!(quote.clone().evaluate(currentScope, manager)));
+					let out = Object::from(Input!(quote.clone().evaluate(currentScope, manager)?));
 					if _type.is_none() {
-						return Expression::Value(Box::new(out));
+						return Ok(Expression::Value(Box::new(out)));
 					} else {
 						match _type.clone().unwrap().token {
-							TokenTable::KeywordSayı => Expression::from(Object::Number(out.forceIntoNumber())),
+							TokenTable::KeywordSayı => match out.forceIntoText().value.trim().parse::<f64>() {
+								Ok(number) => Expression::from(number),
+								Err(_) => {
+									return Err(TipHatası::expected(
+										"Girdinin bir Sayı olması bekleniyordu.".to_owned(),
+										format!("{:?}", out),
+										manager.source(),
+										manager.location(),
+									))?;
+								}
+							},
 							TokenTable::KeywordMetin => Expression::from(Object::Text(out.forceIntoText())),
 							TokenTable::KeywordMantıksal => Expression::from(Object::Bool(out.forceIntoBool())),
 							_ => Expression::Value(Box::new(out)),
@@ -415,18 +657,29 @@ impl Atom {
 					let out = match method {
 						RandomizerType::Number => {
 							let span = span.clone().unwrap_or((Expression::from(0.0), Expression::from(1.0)));
-							let from_val = span.0.evaluate(currentScope, manager).forceIntoNumber().value.floor() as i64;
-							let to_val = span.1.evaluate(currentScope, manager).forceIntoNumber().value.floor() as i64;
+							let from_val = span.0.evaluate(currentScope, manager)?.forceIntoNumber().value.floor() as i64;
+							let to_val = span.1.evaluate(currentScope, manager)?.forceIntoNumber().value.floor() as i64;
+							if from_val > to_val {
+								return Err(TipHatası::expected(
+									"Alt sınırın üst sınırdan büyük olmaması bekleniyordu.".to_owned(),
+									format!("{from_val}, {to_val}"),
+									manager.source(),
+									manager.location(),
+								)
+								.into());
+							}
 							let mut rng = rand::rng();
 							let rand_num = rng.random_range(from_val..=to_val);
 							Expression::from(rand_num as f64)
 						}
 						RandomizerType::Letter => {
-							panic!("Random letter generation is not implemented yet.");
+							let letters = LETTERARRAY.chars().collect::<Vec<_>>();
+							let mut rng = rand::rng();
+							Expression::from(letters[rng.random_range(0..letters.len())].to_string())
 						}
 						RandomizerType::Boolean { chance } => {
 							let mut rng = rand::rng();
-							let rand_bool = rng.random_bool((chance.evaluate(currentScope, manager).forceIntoNumber().value / 100.0).clamp(0.0, 1.0));
+							let rand_bool = rng.random_bool((chance.evaluate(currentScope, manager)?.forceIntoNumber().value / 100.0).clamp(0.0, 1.0));
 							Expression::from(rand_bool)
 						}
 					};
@@ -436,9 +689,10 @@ impl Atom {
 				YieldInstructionEnum::CallFunction { name, args } => {
 					let mut args_evaluated = Vec::new();
 					for arg in args {
-						args_evaluated.push(Expression::from(arg.evaluate(currentScope, manager)));
+						args_evaluated.push(Expression::from(arg.evaluate(currentScope, manager)?));
 					}
-					match manager.call_function(currentScope, name, args_evaluated) {
+					let (src, span) = (manager.source(), manager.location());
+					match manager.call_function(currentScope, name, args_evaluated, src, span)? {
 						Some(result) => Expression::from(result),
 						None => Expression::falsy(),
 					}
@@ -447,42 +701,21 @@ impl Atom {
 					println!("Random var instruction: {name}");
 					Expression::falsy()
 				}
-				YieldInstructionEnum::Index(name, val) => {
-					println!("hi");
-					let index_val = val.resolve(currentScope, manager);
-					let index = index_val.evaluate(currentScope, manager).forceIntoNumber().value as i64;
-					let obj = manager.get_var(currentScope, name.clone());
-					if obj.is_none() {
-						panic!("Variable {name} not found in scope {currentScope}");
-					}
-					let obj = obj.unwrap();
-					match obj {
-						Object::Text(text) => {
-							let len = text.value.len() as i64;
-							let i = if index < 0 { len + index } else { index };
-							if i < 0 || i >= len {
-								println!("Index out of bounds: {i} for {len}");
-								return Expression::falsy();
-							}
-							Expression::from(text.value.chars().nth(i as usize).unwrap().to_string())
-						}
-						Object::Array(list) => {
-							let len = list.value.len() as i64;
-							let i = if index < 0 { len + index } else { index };
-							if i < 0 || i >= len {
-								println!("Index out of bounds: {i} for {len}");
-								return Expression::falsy();
-							}
-							Expression::from(list[i as usize].clone())
-						}
-						_ => {
-							println!("Cannot index type: {obj:?}");
-							Expression::falsy()
-						}
+				YieldInstructionEnum::Index(name, path) => {
+					let Some(mut obj) = manager.get_var(currentScope, name) else {
+						return Err(DegiskenBulunamadı {
+							src: manager.source(),
+							bad_bit: manager.location(),
+						})?;
+					};
+					for accessor in path {
+						let accessor = accessor.evaluate(currentScope, manager)?;
+						obj = obj.get_at(&accessor, manager.source(), manager.location())?;
 					}
+					Expression::from(obj)
 				}
 			},
-		}
+		})
 	}
 }
 
@@ -501,7 +734,7 @@ pub enum YieldInstructionEnum {
 		name: String,
 		args: Vec<Expression>,
 	},
-	Index(String, Box<Atom>)
+	Index(String, Vec<Accessor>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -535,6 +768,16 @@ pub enum InstructionEnum {
 	WhileTrue {
 		scope_pointer: usize,
 	},
+	/// Runs the block, then stops once the condition holds. (`... olana kadar tekrarla`)
+	RepeatUntil {
+		condition: Atom,
+		scope_pointer: usize,
+	},
+	/// Checks the condition first and runs the block as long as it doesn't hold. (`... olmadığı sürece tekrarla`)
+	RepeatWhile {
+		condition: Atom,
+		scope_pointer: usize,
+	},
 	IfBlock {
 		scope_pointer: usize,
 		condition: Atom,
@@ -547,6 +790,20 @@ pub enum InstructionEnum {
 		scope_pointer: usize,
 	},
 	Condition(ConditionBlock),
+	/// `dene`
+	TryBlock {
+		scope_pointer: usize,
+	},
+	/// `yakala` or `yakala hata`, the error object is bound to the given name.
+	CatchBlock {
+		name: Option<String>,
+		scope_pointer: usize,
+	},
+	/// `sonunda`, runs whether the `dene` block failed or not.
+	FinallyBlock {
+		scope_pointer: usize,
+	},
+	TryCatch(TryStructure),
 	Function {
 		name: String,
 		args: Vec<ParameterData>,
@@ -558,6 +815,29 @@ pub enum InstructionEnum {
 		args: Vec<Atom>,
 	},
 	VariableDeclaration(String, Atom, AssignmentMethod),
+	/// `a{0} = değer`, `a{1}{2} += 1`, `a{1:3} = [..]`
+	IndexAssignment {
+		name: String,
+		path: Vec<Accessor>,
+		value: Atom,
+		method: AssignmentMethod,
+	},
+	/// `içe aktar "dosya.zen"`, makes the module's top level reachable as `namespace.isim`.
+	Import {
+		path: String,
+		namespace: String,
+	},
+	/// `hata fırlat "mesaj"`
+	Throw(Atom),
+	/// `test "açıklama":`, its body only runs under `zen test`.
+	Test {
+		name: String,
+		scope_pointer: usize,
+	},
+	/// `doğrula ifade`, fails unless the value is truthy.
+	Assert(Atom),
+	/// `eşit_olmalı a, b`, fails unless both values are equal.
+	AssertEqual(Atom, Atom),
 	Break,
 	Continue,
 	Return(Atom),
@@ -571,7 +851,13 @@ impl InstructionEnum {
 				| InstructionEnum::ElifBlock { .. }
 				| InstructionEnum::ElseBlock { .. }
 				| InstructionEnum::WhileTrue { .. }
+				| InstructionEnum::RepeatUntil { .. }
+				| InstructionEnum::RepeatWhile { .. }
 				| InstructionEnum::Repeat { .. }
+				| InstructionEnum::TryBlock { .. }
+				| InstructionEnum::CatchBlock { .. }
+				| InstructionEnum::FinallyBlock { .. }
+				| InstructionEnum::Test { .. }
 		)
 	}
 	pub fn as_block_action(&self) -> ScopeAction {
@@ -580,6 +866,8 @@ impl InstructionEnum {
 			InstructionEnum::ElifBlock { condition, .. } => ScopeAction::Condition(condition.clone()),
 			InstructionEnum::ElseBlock { .. } => ScopeAction::Condition(Expression::truthy().into()),
 			InstructionEnum::WhileTrue { .. } => ScopeAction::WhileTrue,
+			InstructionEnum::RepeatUntil { condition, .. } => ScopeAction::RepeatUntil(condition.clone()),
+			InstructionEnum::RepeatWhile { condition, .. } => ScopeAction::RepeatWhile(condition.clone()),
 			InstructionEnum::Repeat { repeat_count, scope_pointer } => ScopeAction::Repeat(repeat_count.clone()),
 			InstructionEnum::For {
 				from,
@@ -601,6 +889,10 @@ impl InstructionEnum {
 				name: name.clone(),
 				args: args.clone(),
 			},
+			InstructionEnum::TryBlock { .. } => ScopeAction::Try,
+			InstructionEnum::CatchBlock { name, .. } => ScopeAction::Catch(name.clone()),
+			InstructionEnum::FinallyBlock { .. } => ScopeAction::Finally,
+			InstructionEnum::Test { name, .. } => ScopeAction::Test(name.clone()),
 			_ => panic!(),
 		}
 	}
@@ -620,30 +912,264 @@ impl InstructionEnum {
 			| InstructionEnum::ElifBlock { scope_pointer, .. }
 			| InstructionEnum::ElseBlock { scope_pointer, .. }
 			| InstructionEnum::WhileTrue { scope_pointer }
+			| InstructionEnum::RepeatUntil { scope_pointer, .. }
+			| InstructionEnum::RepeatWhile { scope_pointer, .. }
 			| InstructionEnum::Function { scope_pointer, .. }
 			| InstructionEnum::For { scope_pointer, .. }
 			| InstructionEnum::ForIn { scope_pointer, .. }
-			| InstructionEnum::Repeat { scope_pointer, .. } => *scope_pointer = pointer,
+			| InstructionEnum::Repeat { scope_pointer, .. }
+			| InstructionEnum::TryBlock { scope_pointer }
+			| InstructionEnum::CatchBlock { scope_pointer, .. }
+			| InstructionEnum::FinallyBlock { scope_pointer }
+			| InstructionEnum::Test { scope_pointer, .. } => *scope_pointer = pointer,
 			_ => panic!(),
 		}
 	}
 }
 
+/// One parsed line of a file and, for block headers, the lines indented under it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+	pub instr: InstructionEnum,
+	pub span: Span,
+	pub body: Vec<Statement>,
+}
+
+impl Statement {
+	/// The statement's span stretched over its whole body.
+	pub fn extent(&self) -> Span {
+		self.body.last().map_or(self.span, |last| self.span.to(last.extent()))
+	}
+}
+
 pub fn tokenize(input: &str) -> Vec<TokenData> {
+	tokenize_at(input, 0, 0, 1)
+}
+
+/// Tokenizes a piece of `file` that starts at byte `offset` on `line`, so that token spans point into the whole file.
+pub fn tokenize_at(input: &str, file: usize, offset: usize, line: usize) -> Vec<TokenData> {
 	let mut lexer = TokenTable::lexer(input);
 	let mut tokens = Vec::new();
 
 	while let Some(token) = lexer.next() {
+		let span = Span::new(file, lexer.span().start + offset..lexer.span().end + offset, line);
+		if let Ok(TokenTable::StringLiteral) = token {
+			let raw = lexer.slice();
+			let quote = if raw.len() >= 6 && raw.starts_with("\"\"\"") { 3 } else { 1 };
+			let content = &raw[quote..raw.len() - quote];
+			if content.contains('{') || content.contains('}') {
+				tokens.extend(expand_interpolation(content, span, quote));
+			} else {
+				tokens.push(TokenData::new(TokenTable::StringLiteral, unescape(content), span));
+			}
+			continue;
+		}
 		tokens.push(TokenData {
 			isOk: token.is_ok(),
 			token: token.clone().unwrap_or(TokenTable::Error),
-			slice: match token {
-				Ok(TokenTable::StringLiteral) => lexer.slice().to_string().remove_quotes(),
-				_ => lexer.slice().to_string(),
-			},
-			span: lexer.span(),
+			slice: lexer.slice().to_string(),
+			span,
 		});
 	}
 	// println!("Tokenizer: {tokens:#?}");
 	tokens
 }
+
+/// Why `tokenize_file` could not lay out a file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayoutError {
+	/// The line is indented with spaces, only tabs open blocks.
+	Spaces(Span),
+	/// The line is more than one level deeper than the line before it, only an error in strict mode.
+	TooDeep(Span),
+	/// The file ended while this bracket was still open.
+	Unclosed(Span),
+}
+
+/// Tokenizes a whole file and turns its layout into tokens: every logical line ends with a `Newline` (`;` ends one
+/// too, without changing the indentation) and going a tab deeper or shallower becomes `Indent` / `Dedent`. Newlines
+/// inside open brackets do not end the line, tabs and comments are dropped.
+pub fn tokenize_file(source: &str, file: usize, strict: bool) -> Result<Vec<TokenData>, LayoutError> {
+	let line_starts = std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1)).collect::<Vec<_>>();
+	let mut tokens = Vec::new();
+	let mut depth = 0usize;
+	// Opening brackets that are not closed yet, the line goes on until they are.
+	let mut brackets: Vec<Span> = Vec::new();
+	// `line_start` is set once a logical line ended, `measure` when the next one starts on a fresh line.
+	let (mut line_start, mut measure) = (true, true);
+	// `Newline` gets an empty span right after the last token, where a line that ended too early is missing something.
+	let mut last_end = Span::new(file, 0..0, 1);
+
+	for mut token in tokenize_at(source, file, 0, 1) {
+		token.span.line = line_starts.partition_point(|x| *x <= token.span.start);
+		match token.token {
+			TokenTable::Tab | TokenTable::Comment => continue,
+			TokenTable::Newline | TokenTable::Semicolon if brackets.is_empty() => {
+				if !line_start {
+					tokens.push(TokenData::new(TokenTable::Newline, token.slice, last_end));
+					line_start = true;
+				}
+				measure = measure || token.token == TokenTable::Newline;
+				continue;
+			}
+			TokenTable::Newline => continue,
+			TokenTable::LPAREN | TokenTable::LSQBRACKET | TokenTable::LCRBRACKET => brackets.push(token.span),
+			TokenTable::RPAREN | TokenTable::RSQBRACKET | TokenTable::RCRBRACKET => {
+				brackets.pop();
+			}
+			_ => {}
+		}
+
+		if line_start && measure {
+			let begin = line_starts[token.span.line - 1];
+			let at = |range: Range<usize>| Span::new(file, range, token.span.line);
+			let leading = &source[begin..token.span.start];
+			if leading.contains(' ') {
+				return Err(LayoutError::Spaces(at(begin..token.span.start)));
+			}
+			let mut tabs = leading.matches('\t').count();
+			if tabs > depth + 1 {
+				if strict {
+					// Only the tabs past the one level a new block may open are wrong.
+					return Err(LayoutError::TooDeep(at(begin + depth + 1..begin + tabs)));
+				}
+				tabs = depth + 1;
+			}
+			if tabs > depth {
+				tokens.push(TokenData::new(TokenTable::Indent, "\t".to_owned(), at(begin + depth..begin + tabs)));
+			}
+			for _ in tabs..depth {
+				tokens.push(TokenData::new(TokenTable::Dedent, String::new(), at(token.span.start..token.span.start)));
+			}
+			depth = tabs;
+		}
+		line_start = false;
+		measure = false;
+		last_end = Span { start: token.span.end, ..token.span };
+		tokens.push(token);
+	}
+
+	if let Some(bracket) = brackets.first() {
+		return Err(LayoutError::Unclosed(*bracket));
+	}
+	let end = Span::new(file, source.len()..source.len(), line_starts.len());
+	if !line_start {
+		tokens.push(TokenData::new(TokenTable::Newline, String::new(), last_end));
+	}
+	tokens.extend((0..depth).map(|_| TokenData::new(TokenTable::Dedent, String::new(), end)));
+	Ok(tokens)
+}
+
+/// Splits `"Merhaba {ad}!"` into `InterpolationStart`, text pieces, `{` + the tokens of each embedded expression + `}`
+/// and `InterpolationEnd`. Embedded tokens keep their position in the file, `{{` and `}}` stand for literal braces.
+fn expand_interpolation(content: &str, literal: Span, quote: usize) -> Vec<TokenData> {
+	let Span { file, line, .. } = literal;
+	let base = literal.start + quote;
+	let at = |range: Range<usize>| Span::new(file, base + range.start..base + range.end, line);
+
+	let mut tokens = vec![TokenData::new(
+		TokenTable::InterpolationStart,
+		"\"".repeat(quote),
+		Span::new(file, literal.start..base, line),
+	)];
+	let mut text = String::new();
+	let mut text_start = 0;
+	let mut i = 0;
+	while i < content.len() {
+		let rest = &content[i..];
+		if rest.starts_with("{{") || rest.starts_with("}}") {
+			text.push_str(&rest[..1]);
+			i += 2;
+			continue;
+		}
+		// Escapes stay in the text for `unescape`, so the brace of `\u{...}` does not open a part.
+		if rest.starts_with('\\') {
+			let len = match rest.strip_prefix("\\u{").and_then(|x| x.find('}')) {
+				Some(close) => close + 4,
+				None => rest.chars().take(2).map(char::len_utf8).sum(),
+			};
+			text.push_str(&rest[..len]);
+			i += len;
+			continue;
+		}
+		if !rest.starts_with('{') {
+			let c = rest.chars().next().unwrap();
+			text.push(c);
+			i += c.len_utf8();
+			continue;
+		}
+
+		if !text.is_empty() {
+			tokens.push(TokenData::new(TokenTable::StringLiteral, unescape(&std::mem::take(&mut text)), at(text_start..i)));
+		}
+		// Find the brace closing this part, map literals may nest more braces inside it.
+		let mut depth = 0;
+		let close = rest.char_indices().find_map(|(j, c)| {
+			match c {
+				'{' => depth += 1,
+				'}' => depth -= 1,
+				_ => {}
+			}
+			(depth == 0).then_some(i + j)
+		});
+		let inner_end = close.unwrap_or(content.len());
+		tokens.push(TokenData::new(TokenTable::LCRBRACKET, "{".to_owned(), at(i..i + 1)));
+		tokens.extend(tokenize_at(&content[i + 1..inner_end], file, base + i + 1, line));
+		if let Some(close) = close {
+			tokens.push(TokenData::new(TokenTable::RCRBRACKET, "}".to_owned(), at(close..close + 1)));
+		}
+		i = (inner_end + 1).min(content.len());
+		text_start = i;
+	}
+	if !text.is_empty() {
+		tokens.push(TokenData::new(TokenTable::StringLiteral, unescape(&text), at(text_start..content.len())));
+	}
+	tokens.push(TokenData::new(
+		TokenTable::InterpolationEnd,
+		"\"".repeat(quote),
+		Span::new(file, literal.end - quote..literal.end, line),
+	));
+	tokens
+}
+
+/// Decodes `\n`, `\t`, `\"`, `\\` and `\u{...}` in the body of a text literal. Unknown escapes are kept as written.
+pub fn unescape(text: &str) -> String {
+	let mut out = String::with_capacity(text.len());
+	let mut chars = text.chars().peekable();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			out.push(c);
+			continue;
+		}
+		match chars.peek().copied() {
+			Some('n') => out.push('\n'),
+			Some('t') => out.push('\t'),
+			Some('"') => out.push('"'),
+			Some('\\') => out.push('\\'),
+			Some('u') => {
+				let rest = chars.clone().skip(1).collect::<String>();
+				let decoded = rest
+					.strip_prefix('{')
+					.and_then(|x| x.split_once('}'))
+					.and_then(|(hex, _)| Some((hex.len(), char::from_u32(u32::from_str_radix(hex, 16).ok()?)?)));
+				match decoded {
+					Some((len, decoded)) => {
+						out.push(decoded);
+						// `u`, `{`, the digits and `}`
+						for _ in 0..len + 3 {
+							chars.next();
+						}
+					}
+					None => out.push('\\'),
+				}
+				continue;
+			}
+			_ => {
+				out.push('\\');
+				continue;
+			}
+		}
+		chars.next();
+	}
+	out
+}
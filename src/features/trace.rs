@@ -0,0 +1,93 @@
+//! `zen run --trace`: reports every instruction the tree interpreter runs, what its conditions evaluated to, which
+//! iteration a loop is on and how assignments changed a variable. The trace goes to standard error so the program's
+//! own output stays where it was, either as colored text or as one JSON object per line.
+
+use std::io::Write;
+
+use colored::Colorize;
+use serde_json::{Value, json};
+
+use crate::{
+	features::{ast, dump, tokenizer::{InstructionEnum, Span}},
+	library::Types::Object,
+	util::ScopeManager::{ExecutionHook, ScopeManager},
+};
+
+/// İzleme çıktısının biçimi
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum TraceFormat {
+	/// Renkli, insanın okuyabileceği metin
+	Text,
+	/// Her satırda bir olay olan JSON (JSON Lines)
+	Json,
+}
+
+#[derive(Debug)]
+pub struct Tracer {
+	format: TraceFormat,
+}
+
+impl Tracer {
+	pub fn new(format: TraceFormat) -> Self {
+		Self { format }
+	}
+
+	/// Writes one event. `fields` are added to the JSON object, `text` is the row of the text format.
+	fn emit(&self, manager: &ScopeManager, span: Span, event: &str, fields: Value, text: String) {
+		let depth = manager.call_depth();
+		let mut stderr = std::io::stderr().lock();
+		let _ = match self.format {
+			TraceFormat::Json => {
+				let mut out = json!({ "event": event, "file": manager.source_name(span.file).unwrap_or_default(), "line": span.line, "depth": depth });
+				if let (Value::Object(out), Value::Object(fields)) = (&mut out, fields) {
+					out.extend(fields);
+				}
+				writeln!(stderr, "{out}")
+			}
+			TraceFormat::Text => writeln!(stderr, "{}{} {text}", "  ".repeat(depth), format!("{:>4} │", span.line).dimmed()),
+		};
+	}
+}
+
+impl ExecutionHook for Tracer {
+	fn before(&mut self, _scope_id: usize, manager: &mut ScopeManager, instr: &InstructionEnum, span: Span) -> miette::Result<()> {
+		if matches!(instr, InstructionEnum::NoOp) {
+			return Ok(());
+		}
+		let code = ast::describe(instr);
+		let (kind, operands) = code.split_once(' ').unwrap_or((&code, ""));
+		let text = format!("{} {operands}", kind.bold()).trim_end().to_owned();
+		self.emit(manager, span, "instruction", json!({ "kind": kind, "code": code }), text);
+		Ok(())
+	}
+
+	fn condition(&mut self, manager: &ScopeManager, span: Span, keyword: &str, result: bool) {
+		let shown = if result { "doğru".green() } else { "yanlış".red() };
+		self.emit(manager, span, "condition", json!({ "keyword": keyword, "result": result }), format!("  {keyword} → {shown}"));
+	}
+
+	fn iteration(&mut self, manager: &ScopeManager, span: Span, count: usize, variable: Option<(&str, &Object)>) {
+		let (fields, text) = match variable {
+			Some((name, value)) => (
+				json!({ "count": count, "variable": name, "value": dump::object(value) }),
+				format!("  {}. tur, {name} = {}", count, ast::object(value)),
+			),
+			None => (json!({ "count": count }), format!("  {}. tur", count)),
+		};
+		self.emit(manager, span, "iteration", fields, text.cyan().to_string());
+	}
+
+	fn assignment(&mut self, manager: &ScopeManager, span: Span, name: &str, old: Option<&Object>, new: &Object) {
+		let text = match old {
+			Some(old) => format!("  {name}: {} → {}", ast::object(old), ast::object(new)),
+			None => format!("  {name}: {}", ast::object(new)),
+		};
+		self.emit(
+			manager,
+			span,
+			"assignment",
+			json!({ "name": name, "old": old.map_or(Value::Null, dump::object), "new": dump::object(new) }),
+			text.yellow().to_string(),
+		);
+	}
+}
@@ -0,0 +1,8 @@
+#![allow(non_snake_case)]
+
+pub mod features;
+pub mod library;
+pub mod parsers;
+pub mod test;
+pub mod util;
+pub mod vm;
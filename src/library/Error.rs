@@ -15,6 +15,28 @@ pub struct GirintiHatası {
 	pub bad_bit: SourceSpan,
 }
 
+#[derive(Error, Debug, Diagnostic)]
+#[error("Boşluklu Girinti")]
+#[diagnostic(help("Girinti için boşluk değil, tab (\\t) kullanın."))]
+pub struct BoslukGirintisi {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Kapanmamış Parantez")]
+#[diagnostic(help("Açılan her parantezin dosya bitmeden kapatıldığından emin olun."))]
+pub struct KapanmamisParantez {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Bu parantez hiç kapatılmadı.")]
+	pub bad_bit: SourceSpan,
+}
+
 #[derive(Error, Debug, Diagnostic)]
 #[error("Değişken Bulunamadı")]
 #[diagnostic(help("Bu değişkenin daha önceden tanımlanmış olduğundan emin olun."))]
@@ -70,7 +92,7 @@ pub struct CokFazlaArguman {
 }
 
 #[derive(Error, Debug, Diagnostic)]
-#[error("Çok Fazla Argüman Hatası")]
+#[error("Eksik Argüman Hatası")]
 pub struct EksikArguman {
 	#[source_code]
 	pub src: NamedSource<String>,
@@ -82,6 +104,200 @@ pub struct EksikArguman {
 	pub expected: Option<String>,
 }
 
+#[derive(Error, Debug, Diagnostic)]
+#[error("İndeks Hatası")]
+#[diagnostic(help("{index} indeksi, uzunluğu {length} olan bir değer için geçersiz. Negatif indeksler sondan saymaya başlar."))]
+pub struct IndeksHatası {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub index: i64,
+
+	pub length: usize,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Anahtar Bulunamadı")]
+#[diagnostic(help("Sözlükte {key} anahtarı bulunmuyor. Önce bir değer atamayı deneyin."))]
+pub struct AnahtarBulunamadı {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub key: String,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Özyineleme Sınırı Aşıldı")]
+#[diagnostic(help("{function} fonksiyonu iç içe {limit} defadan fazla çağrıldı. Fonksiyonun bir noktada durduğundan emin olun."))]
+pub struct OzyinelemeHatası {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub function: String,
+
+	pub limit: usize,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Yığın Tükendi")]
+#[diagnostic(help("{function} fonksiyonu iç içe {depth} defa çağrıldığında yorumlayıcının yığını tükendi. Özyinelemenin derinliğini azaltmayı deneyin."))]
+pub struct YiginTukendi {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub function: String,
+
+	pub depth: usize,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Modül Bulunamadı")]
+#[diagnostic(help("{path} dosyası okunamadı. Yolun, içe aktaran dosyanın bulunduğu klasöre göre yazıldığından emin olun."))]
+pub struct ModulBulunamadı {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub path: String,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Döngüsel İçe Aktarma")]
+#[diagnostic(help("Modüller birbirini içe aktarıyor: {chain}"))]
+pub struct DonguselIceAktarma {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub chain: String,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("{message}")]
+#[diagnostic(help("Bu hata `hata fırlat` ile fırlatıldı. Yakalamak için `dene` / `yakala` bloklarını kullanın."))]
+pub struct FirlatilanHata {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub message: String,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Doğrulama Başarısız")]
+#[diagnostic(help("`doğrula` ile denetlenen ifade yanlış çıktı."))]
+pub struct DogrulamaBasarisiz {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Değerler Eşit Değil")]
+#[diagnostic(help("Soldaki değer: {left}\nSağdaki değer: {right}"))]
+pub struct EsitDegil {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub left: String,
+	pub right: String,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Döngü Dışında `{keyword}`")]
+#[diagnostic(help("`durdur` ve `devam et` yalnızca bir döngünün içinde kullanılabilir."))]
+pub struct DonguDisinda {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub keyword: String,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Fonksiyon Dışında `döndür`")]
+#[diagnostic(help("`döndür` yalnızca bir fonksiyonun içinde kullanılabilir."))]
+pub struct FonksiyonDisindaDondur {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("`eğer` Olmadan `değilse`")]
+#[diagnostic(help("`değilse` ve `değilse ve` blokları bir `eğer` ya da `değilse ve` bloğunun hemen ardından gelmelidir."))]
+pub struct EgerOlmadanDegilse {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("`dene` Olmadan `yakala`")]
+#[diagnostic(help("`yakala` bloğu bir `dene` bloğunun, `sonunda` bloğu da bir `dene` ya da `yakala` bloğunun hemen ardından gelmelidir."))]
+pub struct DeneOlmadanYakala {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Hata buradan kaynaklandı.")]
+	pub bad_bit: SourceSpan,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Erişilemeyen Kod")]
+#[diagnostic(severity(Warning), help("Bu satırlar `{keyword}` komutundan sonra geldiği için hiçbir zaman çalışmaz."))]
+pub struct ErisilemeyenKod {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Bu kod hiç çalışmayacak.")]
+	pub bad_bit: SourceSpan,
+
+	pub keyword: String,
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Kullanılmayan Değişken: {name}")]
+#[diagnostic(severity(Warning), help("Bu değişkene değer veriliyor ama hiç okunmuyor. Bilerek yapıyorsanız adını `_` ile başlatın."))]
+pub struct KullanilmayanDegisken {
+	#[source_code]
+	pub src: NamedSource<String>,
+
+	#[label("Burada tanımlandı.")]
+	pub bad_bit: SourceSpan,
+
+	pub name: String,
+}
+
 #[derive(Error, Debug, Diagnostic)]
 #[error("Token Hatası")]
 #[diagnostic(help("Beklenen token: {expected:#?}\nAlınan token: {got:?}"))]
@@ -9,7 +9,7 @@ use miette::{NamedSource, SourceSpan};
 use num::iter::Range;
 use num::pow::Pow;
 
-use crate::features::tokenizer::{RemoveQuotes, TokenData, TokenTable};
+use crate::features::tokenizer::{Accessor, AssignmentMethod, RemoveQuotes, TokenData, TokenTable};
 use crate::library::Methods::Throw;
 use crate::parsers::Parsers::Expression;
 use crate::util::ScopeManager::ScopeManager;
@@ -19,9 +19,10 @@ use std::ops::{Add, Div, Index, Mul, Rem, Sub};
 use std::rc::Rc;
 use std::{fmt::Display, num::ParseFloatError, str::FromStr};
 
-use super::Error::TipHatası;
+use super::Error::{AnahtarBulunamadı, IndeksHatası, TipHatası};
 
-static LETTERARRAY: &'static str = "abcçdefgğhıijklmnoöprsştuüvyzABCÇDEFGĞHIİJKLMNOÖPRSŞTUÜVYZ";
+/// Letters `rastgele harf` picks from.
+pub static LETTERARRAY: &str = "abcçdefgğhıijklmnoöprsştuüvyzABCÇDEFGĞHIİJKLMNOÖPRSŞTUÜVYZ";
 
 /// The exact equals:
 /// - Number = f64
@@ -88,8 +89,8 @@ pub struct ResolvedParameterData {
 }
 
 impl ParameterData {
-	pub fn toResolved(&self, currentScope: usize, manager: &mut ScopeManager) -> ResolvedParameterData {
-		ResolvedParameterData {
+	pub fn toResolved(&self, currentScope: usize, manager: &mut ScopeManager) -> miette::Result<ResolvedParameterData> {
+		Ok(ResolvedParameterData {
 			name: self.name.clone(),
 			data_type: self.data_type.clone().map(|token| match token.token {
 				TokenTable::KeywordSayı => ObjectType::Number,
@@ -97,8 +98,8 @@ impl ParameterData {
 				TokenTable::KeywordMantıksal => ObjectType::Boolean,
 				_ => panic!("Unsupported TokenData variant for conversion to ObjectType: {:?}", token),
 			}),
-			default_value: self.default_value.clone().map(|expr| expr.evaluate(currentScope, manager)),
-		}
+			default_value: self.default_value.clone().map(|expr| expr.evaluate(currentScope, manager)).transpose()?,
+		})
 	}
 }
 
@@ -107,6 +108,8 @@ pub struct Function {
 	pub name: String,
 	pub args: Vec<ResolvedParameterData>,
 	pub scope_pointer: usize,
+	/// Source file the function was declared in.
+	pub file: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -117,6 +120,7 @@ pub enum ObjectType {
 	Variable,
 	Null,
 	Array,
+	Map,
 }
 
 impl Display for ObjectType {
@@ -128,6 +132,7 @@ impl Display for ObjectType {
 			ObjectType::Variable => "Değişken",
 			ObjectType::Null => "NIL",
 			ObjectType::Array => "Dizi",
+			ObjectType::Map => "Sözlük",
 		};
 		write!(f, "{}", type_str)
 	}
@@ -140,6 +145,7 @@ pub enum Object {
 	Bool(Boolean),
 	Variable(String),
 	Array(Array),
+	Map(Map),
 	Null,
 }
 
@@ -152,6 +158,7 @@ impl Object {
 			Object::Variable(_) => ObjectType::Variable,
 			Object::Null => ObjectType::Null,
 			Object::Array(_) => ObjectType::Array,
+			Object::Map(_) => ObjectType::Map,
 		}
 	}
 
@@ -193,6 +200,7 @@ impl Object {
 			Object::Number(val) => val.value != 0.0,
 			Object::Text(val) => !val.value.is_empty(),
 			Object::Array(val) => !val.value.is_empty(),
+			Object::Map(val) => !val.value.is_empty(),
 			Object::Variable(_) => true,
 			Object::Null => false,
 		}
@@ -275,6 +283,199 @@ impl Object {
 		}
 	}
 
+	/// Element count of indexable values. Texts are counted in characters, not bytes.
+	pub fn length(&self) -> Option<usize> {
+		match self {
+			Object::Text(val) => Some(val.value.chars().count()),
+			Object::Array(val) => Some(val.value.len()),
+			Object::Map(val) => Some(val.value.len()),
+			_ => None,
+		}
+	}
+
+	fn expectToBeIndexable(&self, src: NamedSource<String>, span: SourceSpan) -> Result<usize, TipHatası> {
+		self.length().ok_or_else(|| {
+			TipHatası::expected(
+				"Verilen tipin bir dizi, metin veya sözlük olması bekleniyordu.".to_string(),
+				format!("{}", self.get_type()),
+				src,
+				span,
+			)
+		})
+	}
+
+	/// Resolves a (possibly negative) index against this value, returning the position or an `IndeksHatası`.
+	fn resolveIndex(&self, index: &Object, src: NamedSource<String>, span: SourceSpan) -> miette::Result<usize> {
+		let length = self.expectToBeIndexable(src.clone(), span)?;
+		let index = index.expectToBeNumber(src.clone(), span)?.value as i64;
+		let position = if index < 0 { length as i64 + index } else { index };
+		if position < 0 || position >= length as i64 {
+			return Err(IndeksHatası {
+				src,
+				bad_bit: span,
+				index,
+				length,
+			})?;
+		}
+		Ok(position as usize)
+	}
+
+	/// Resolves slice bounds the way Python does: negative bounds count from the end and everything is clamped.
+	fn resolveSlice(&self, from: &Option<Object>, to: &Option<Object>, src: NamedSource<String>, span: SourceSpan) -> miette::Result<(usize, usize)> {
+		let length = self.expectToBeIndexable(src.clone(), span)? as i64;
+		let bound = |value: &Option<Object>, default: i64| -> miette::Result<usize> {
+			let index = match value {
+				Some(value) => value.expectToBeNumber(src.clone(), span)?.value as i64,
+				None => default,
+			};
+			let position = if index < 0 { length + index } else { index };
+			Ok(position.clamp(0, length) as usize)
+		};
+		let from = bound(from, 0)?;
+		let to = bound(to, length)?;
+		Ok((from, usize::max(from, to)))
+	}
+
+	pub fn get_at(&self, accessor: &Accessor<Object>, src: NamedSource<String>, span: SourceSpan) -> miette::Result<Object> {
+		match (self, accessor) {
+			// A missing key is an error like an index out of range, a typo in a key shouldn't go unnoticed.
+			(Object::Map(map), Accessor::Index(key)) => Ok(map.get(key).cloned().ok_or_else(|| AnahtarBulunamadı {
+				src,
+				bad_bit: span,
+				key: format!("{}", key),
+			})?),
+			(Object::Text(text), Accessor::Index(index)) => {
+				let position = self.resolveIndex(index, src, span)?;
+				Ok(Object::from(text.value.chars().nth(position).unwrap().to_string()))
+			}
+			(Object::Array(list), Accessor::Index(index)) => Ok(list[self.resolveIndex(index, src, span)?].clone()),
+			(Object::Text(text), Accessor::Slice(from, to)) => {
+				let (from, to) = self.resolveSlice(from, to, src, span)?;
+				Ok(Object::from(text.value.chars().skip(from).take(to - from).collect::<String>()))
+			}
+			(Object::Array(list), Accessor::Slice(from, to)) => {
+				let (from, to) = self.resolveSlice(from, to, src, span)?;
+				Ok(Object::from(list.value[from..to].to_vec()))
+			}
+			(Object::Map(_), Accessor::Slice(..)) => Err(TipHatası::expected(
+				"Dilimleme sadece diziler ve metinler için kullanılabilir.".to_string(),
+				format!("{}", self.get_type()),
+				src,
+				span,
+			))?,
+			_ => Err(self.expectToBeIndexable(src, span).unwrap_err())?,
+		}
+	}
+
+	/// `öğe içinde değer`: whether a map has the key, an array has the element or a text has the piece of text.
+	pub fn contains(&self, item: &Object, src: NamedSource<String>, span: SourceSpan) -> miette::Result<bool> {
+		match self {
+			Object::Map(map) => Ok(map.get(item).is_some()),
+			Object::Array(list) => Ok(list.value.contains(item)),
+			Object::Text(text) => Ok(text.value.contains(&item.expectToBeText(src, span)?.value)),
+			_ => Err(self.expectToBeIndexable(src, span).unwrap_err())?,
+		}
+	}
+
+	pub fn set_at(&mut self, accessor: &Accessor<Object>, value: Object, src: NamedSource<String>, span: SourceSpan) -> miette::Result<()> {
+		let self_type = self.get_type();
+		match accessor {
+			Accessor::Index(key) => {
+				let position = match self {
+					Object::Map(_) => 0,
+					_ => self.resolveIndex(key, src.clone(), span)?,
+				};
+				match self {
+					Object::Map(map) => {
+						if !Map::is_valid_key(key) {
+							return Err(TipHatası::expected(
+								"Sözlük anahtarlarının bir sayı veya metin olması bekleniyordu.".to_string(),
+								format!("{}", key.get_type()),
+								src,
+								span,
+							))?;
+						}
+						map.insert(key.clone(), value);
+					}
+					Object::Array(list) => list.value[position] = value,
+					Object::Text(text) => {
+						let replacement = value.expectToBeText(src, span)?.value.clone();
+						text.value = text
+							.value
+							.chars()
+							.enumerate()
+							.map(|(i, c)| if i == position { replacement.clone() } else { c.to_string() })
+							.collect();
+					}
+					_ => unreachable!(),
+				}
+			}
+			Accessor::Slice(from, to) => {
+				if let Object::Map(_) = self {
+					return Err(TipHatası::expected(
+						"Dilimleme sadece diziler ve metinler için kullanılabilir.".to_string(),
+						format!("{}", self_type),
+						src,
+						span,
+					))?;
+				}
+				let (from, to) = self.resolveSlice(from, to, src.clone(), span)?;
+				match self {
+					Object::Array(list) => {
+						let Object::Array(replacement) = value else {
+							return Err(TipHatası::expected(
+								"Dizi dilimlerine sadece dizi atanabilir.".to_string(),
+								format!("{}", value.get_type()),
+								src,
+								span,
+							))?;
+						};
+						list.value.splice(from..to, replacement.value);
+					}
+					Object::Text(text) => {
+						let replacement = value.expectToBeText(src, span)?.value.clone();
+						let chars = text.value.chars().collect::<Vec<_>>();
+						text.value = chars[..from].iter().collect::<String>() + &replacement + &chars[to..].iter().collect::<String>();
+					}
+					_ => unreachable!(),
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Assigns `value` to the element reached by following `path`, e.g. `a{1}{2} += 5`.
+	pub fn assign_at(
+		&mut self,
+		path: &[Accessor<Object>],
+		method: &AssignmentMethod,
+		value: Object,
+		src: NamedSource<String>,
+		span: SourceSpan,
+	) -> miette::Result<()> {
+		match path {
+			[] => Ok(()),
+			[last] => {
+				let previous = match (method, &*self, last) {
+					(AssignmentMethod::Set, ..) => None,
+					(_, Object::Map(map), Accessor::Index(key)) => Some(map.get(key).cloned().ok_or_else(|| AnahtarBulunamadı {
+						src: src.clone(),
+						bad_bit: span,
+						key: format!("{}", key),
+					})?),
+					_ => Some(self.get_at(last, src.clone(), span)?),
+				};
+				let value = method.apply(previous, value, src.clone(), span)?;
+				self.set_at(last, value, src, span)
+			}
+			[first, rest @ ..] => {
+				let mut child = self.get_at(first, src.clone(), span)?;
+				child.assign_at(rest, method, value, src.clone(), span)?;
+				self.set_at(first, child, src, span)
+			}
+		}
+	}
+
 	pub fn forceIntoNumber(&self) -> Number {
 		match self {
 			Object::Number(val) => val.clone(),
@@ -283,6 +484,7 @@ impl Object {
 			Object::Variable(val) => Number::from(val.parse::<f64>().unwrap_or(0.0)),
 			Object::Null => Number::from(0.0),
 			Object::Array(val) => Number::from(if val.value.is_empty() { 0.0 } else { 1.0 }),
+			Object::Map(val) => Number::from(if val.value.is_empty() { 0.0 } else { 1.0 }),
 		}
 	}
 
@@ -294,6 +496,7 @@ impl Object {
 			Object::Variable(val) => Text::from(val.clone()),
 			Object::Null => Text::from("NIL".to_string()),
 			Object::Array(val) => Text::from(val.value.iter().map(|x| format!("{}", x)).collect::<Vec<_>>().join(", ")),
+			Object::Map(val) => Text::from(format!("{}", val)),
 		}
 	}
 
@@ -309,6 +512,7 @@ impl Object {
 			Object::Variable(val) => Boolean::from(!val.remove_quotes().is_empty()),
 			Object::Null => Boolean::from(false),
 			Object::Array(val) => Boolean::from(val.value.is_empty()),
+			Object::Map(val) => Boolean::from(!val.value.is_empty()),
 		}
 	}
 
@@ -320,6 +524,7 @@ impl Object {
 			Object::Variable(val) => Array::from(vec![Object::Variable(val.clone())]),
 			Object::Null => Array::from(vec![]),
 			Object::Array(val) => val.clone(),
+			Object::Map(val) => Array::from(val.keys()),
 		}
 	}
 
@@ -334,6 +539,7 @@ impl Object {
 			Object::Variable(val) => Err(()), // I mean, if you try to force a variable into a number, you have problems.
 			Object::Null => Err(()),
 			Object::Array(val) => Err(()),
+			Object::Map(_) => Err(()),
 		}
 	}
 
@@ -345,6 +551,7 @@ impl Object {
 			Object::Variable(val) => Err(()),
 			Object::Null => Err(()),
 			Object::Array(val) => Err(()),
+			Object::Map(_) => Err(()),
 		}
 	}
 
@@ -356,6 +563,7 @@ impl Object {
 			Object::Variable(_) => Err(()),
 			Object::Null => Err(()),
 			Object::Array(val) => Err(()),
+			Object::Map(_) => Err(()),
 		}
 	}
 }
@@ -525,6 +733,36 @@ pub enum ZenError {
 	DivisionByZeroError,
 	TypeError,
 	IndentationError,
+	SyntaxError,
+	ArgumentError,
+	IndexError,
+	KeyError,
+	RecursionError,
+	ImportError,
+	AssertionError,
+	CheckError,
+}
+
+impl ZenError {
+	/// The name programs see in the `tür` field of a caught error. Kept stable so that it can be compared against.
+	pub fn name(&self) -> &'static str {
+		match self {
+			ZenError::UnknownError => "Bilinmeyen Hata",
+			ZenError::GeneralError => "Genel Hata",
+			ZenError::NotDeclaredError => "Tanımsız Ad",
+			ZenError::DivisionByZeroError => "Sıfıra Bölme",
+			ZenError::TypeError => "Tip Hatası",
+			ZenError::IndentationError => "Girinti Hatası",
+			ZenError::SyntaxError => "Sözdizimi Hatası",
+			ZenError::ArgumentError => "Argüman Hatası",
+			ZenError::IndexError => "İndeks Hatası",
+			ZenError::KeyError => "Anahtar Hatası",
+			ZenError::RecursionError => "Özyineleme Hatası",
+			ZenError::ImportError => "İçe Aktarma Hatası",
+			ZenError::AssertionError => "Doğrulama Hatası",
+			ZenError::CheckError => "Denetim Hatası",
+		}
+	}
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -580,6 +818,33 @@ pub struct Array {
 	pub value: Vec<Object>,
 }
 
+/// Insertion ordered key/value store. Keys can only be numbers or texts.
+#[derive(Debug, Clone)]
+pub struct Map {
+	pub value: Vec<(Object, Object)>,
+}
+
+impl Map {
+	pub fn is_valid_key(key: &Object) -> bool {
+		matches!(key, Object::Number(_) | Object::Text(_))
+	}
+
+	pub fn get(&self, key: &Object) -> Option<&Object> {
+		self.value.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+	}
+
+	pub fn insert(&mut self, key: Object, value: Object) {
+		match self.value.iter_mut().find(|(k, _)| *k == key) {
+			Some(entry) => entry.1 = value,
+			None => self.value.push((key, value)),
+		}
+	}
+
+	pub fn keys(&self) -> Vec<Object> {
+		self.value.iter().map(|(k, _)| k.clone()).collect()
+	}
+}
+
 // ------------------------------------------ Trait Implements ------------------------------------------
 
 impl From<f64> for Object {
@@ -793,6 +1058,17 @@ impl From<Array> for Object {
 		Object::Array(value)
 	}
 }
+impl From<Map> for Object {
+	fn from(value: Map) -> Self {
+		Object::Map(value)
+	}
+}
+
+impl PartialEq for Map {
+	fn eq(&self, other: &Self) -> bool {
+		self.value.len() == other.value.len() && self.value.iter().all(|(k, v)| other.get(k) == Some(v))
+	}
+}
 
 impl Display for Number {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -827,6 +1103,13 @@ impl Display for Array {
 	}
 }
 
+impl Display for Map {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let elements = self.value.iter().map(|(k, v)| format!("{}: {}", k, v)).collect::<Vec<_>>();
+		write!(f, "{{{}}}", elements.join(", "))
+	}
+}
+
 impl Display for Object {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -835,6 +1118,7 @@ impl Display for Object {
 			Object::Text(val) => write!(f, "{}", val),
 			Object::Variable(val) => write!(f, "{}", val),
 			Object::Array(val) => write!(f, "{}", val),
+			Object::Map(val) => write!(f, "{}", val),
 			Object::Null => write!(f, "NIL"),
 		}
 	}
@@ -906,3 +1190,19 @@ pub enum TimeUnit {
 	Month,
 	Year,
 }
+
+impl TimeUnit {
+	/// How long `bekle <amount> <unit>` waits.
+	pub fn duration(&self, amount: f64) -> std::time::Duration {
+		match self {
+			TimeUnit::Millisecond => std::time::Duration::from_millis(amount as u64),
+			TimeUnit::Second => std::time::Duration::from_secs(amount as u64),
+			TimeUnit::Minute => std::time::Duration::from_secs(amount as u64 * 60),
+			TimeUnit::Hour => std::time::Duration::from_secs(amount as u64 * 3600),
+			TimeUnit::Day => std::time::Duration::from_secs(amount as u64 * 86400),
+			TimeUnit::Week => std::time::Duration::from_secs(amount as u64 * 604800),
+			TimeUnit::Month => std::time::Duration::from_secs(amount as u64 * 2592000), // Approximation
+			TimeUnit::Year => std::time::Duration::from_secs(amount as u64 * 31536000), // Approximation
+		}
+	}
+}
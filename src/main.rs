@@ -1,11 +1,6 @@
 #![allow(non_snake_case)]
 
-mod features;
-mod library;
-mod parsers;
 mod stats;
-mod test;
-mod util;
 
 use std::{
 	cell::RefCell,
@@ -13,19 +8,26 @@ use std::{
 	rc::Rc,
 };
 
+use ZenBackend::{features, library, test::run_tests, util};
 use chumsky::{Parser, prelude::*, primitive::Choice};
 use clap::{Parser as ClapParser, Subcommand};
 use colored::Colorize;
 use features::{
-	preprocessor,
+	checker,
+	dump::{self, DumpFormat},
+	formatter, lsp, preprocessor,
 	tokenizer::{self, TokenData, TokenTable},
+	trace::TraceFormat,
 };
 use library::{
 	Methods::Throw,
 	Types::{Severity, ZenError},
 };
-use test::run_tests;
-use util::process;
+use miette::IntoDiagnostic;
+use util::{
+	ScopeManager::{DEFAULT_RECURSION_LIMIT, INTERPRETER_STACK_SIZE},
+	process::{self, Engine},
+};
 
 /// Ana CLI aracı
 #[derive(ClapParser, Debug)]
@@ -51,17 +53,100 @@ enum Commands {
 		#[arg(long, default_value_t = false)]
 		printast: bool,
 
+		/// Ön işlenmiş kaynak kodu göster
 		#[arg(long, default_value_t = false)]
 		printpreprocessoutput: bool,
 
+		/// Kodu çalıştırmadan yalnızca sözdizimini denetle
 		#[arg(long, default_value_t = false)]
 		noexecute: bool,
 
+		/// Çalışan her komutu, koşul sonuçlarını, döngü turlarını ve atamaları standart hataya yazdır
+		#[arg(long, value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "text", value_name = "BİÇİM")]
+		trace: Option<TraceFormat>,
+
 		#[arg(long, default_value_t = true)]
 		strict: bool,
+
+		/// İç içe fonksiyon çağrısı sınırı
+		#[arg(long, default_value_t = DEFAULT_RECURSION_LIMIT)]
+		recursionlimit: usize,
+
+		/// Çalıştırma motoru: ağaç yorumlayıcı ya da bayt kodu sanal makinesi
+		#[arg(long, value_enum, default_value_t = Engine::Tree)]
+		engine: Engine,
+	},
+
+	/// Etkileşimli kabuk (REPL) başlatma komutu
+	Repl,
+
+	/// Programı adım adım çalıştırma ve değişkenleri izleme komutu
+	Debug {
+		/// İşlenecek dosya adı
+		file: String,
+
+		/// Kesme noktası konulacak satırlar, verilmezse program ilk komutta durur
+		#[arg(short, long = "break", value_name = "SATIR")]
+		breakpoints: Vec<usize>,
+
+		#[arg(long, default_value_t = true)]
+		strict: bool,
+
+		/// İç içe fonksiyon çağrısı sınırı
+		#[arg(long, default_value_t = DEFAULT_RECURSION_LIMIT)]
+		recursionlimit: usize,
+	},
+
+	/// Dosyalardaki `test` bloklarını çalıştırma komutu
+	Test {
+		/// Testleri aranacak dosyalar ya da klasörler
+		#[arg(default_value = ".")]
+		files: Vec<String>,
+
+		/// Yalnızca adında bu metin geçen testleri çalıştır
+		#[arg(long)]
+		filter: Option<String>,
 	},
 
-	Test,
+	/// Token akışını ya da AST'yi dışa aktarma komutu
+	#[command(group(clap::ArgGroup::new("içerik").required(true).multiple(true).args(["tokens", "ast"])))]
+	Dump {
+		/// İşlenecek dosya adı
+		file: String,
+
+		/// Token akışını yazdır
+		#[arg(long, default_value_t = false)]
+		tokens: bool,
+
+		/// AST'yi yazdır
+		#[arg(long, default_value_t = false)]
+		ast: bool,
+
+		/// Çıktı biçimi
+		#[arg(long, value_enum, default_value_t = DumpFormat::Json)]
+		format: DumpFormat,
+	},
+
+	/// Kodu çalıştırmadan olası hataları ve uyarıları bulma komutu
+	Check {
+		/// Denetlenecek dosyalar ya da klasörler
+		#[arg(required = true)]
+		files: Vec<String>,
+	},
+
+	/// Düzenleyiciler için dil sunucusunu (LSP) standart girdi/çıktı üzerinden başlatma komutu
+	Lsp,
+
+	/// Kaynak kodu standart biçime getirme komutu
+	Fmt {
+		/// Biçimlendirilecek dosyalar ya da klasörler
+		#[arg(required = true)]
+		files: Vec<String>,
+
+		/// Dosyaları değiştirmeden yalnızca biçimlendirilmiş olup olmadıklarını denetle
+		#[arg(long, default_value_t = false)]
+		check: bool,
+	},
 
 	Stats {
 		#[arg(long, default_value_t = false)]
@@ -69,20 +154,11 @@ enum Commands {
 	},
 }
 
-fn run_zen_file(file: String, verbose: bool, printAst: bool, printPreprocessOutput: bool, noexecute: bool, strict: bool) -> miette::Result<()> {
-	let full_src: String;
-	let contents = match File::open(&file) {
-		Ok(res) => {
-			let lines = read_to_string(&file);
-			match lines {
-				Ok(lines) => {
-					full_src = lines.clone();
-					let mut buffr = Vec::new();
-					for line in lines.lines() {
-						buffr.push(line.to_string());
-					}
-					buffr
-				}
+fn read_zen_file(file: &str) -> String {
+	match File::open(file) {
+		Ok(_) => {
+			match read_to_string(file) {
+				Ok(contents) => contents,
 				Err(_) => {
 					Throw(
 						"Dosya okunmaya çalışırken bir hatayla karşılaşıldı.".to_owned(),
@@ -105,12 +181,90 @@ fn run_zen_file(file: String, verbose: bool, printAst: bool, printPreprocessOutp
 			);
 			unreachable!()
 		}
+	}
+}
+
+fn run_zen_file(
+	file: String,
+	verbose: bool,
+	printAst: bool,
+	printPreprocessOutput: bool,
+	noexecute: bool,
+	trace: Option<TraceFormat>,
+	strict: bool,
+	recursion_limit: usize,
+	engine: Engine,
+) -> miette::Result<()> {
+	let full_src = read_zen_file(&file);
+	let opts = process::Runopts {
+		verbose,
+		strict,
+		print_ast: printAst,
+		print_preprocess_output: printPreprocessOutput,
+		no_execute: noexecute,
+		trace,
 	};
-	process::index(&mut contents.clone(), full_src, verbose, strict, &file)?;
+	process::index(full_src, opts, recursion_limit, &file, engine)?;
 
 	Ok(())
 }
 
+/// Prints what `zen check` finds in `paths`. Only errors make the command fail, warnings are just shown.
+fn check_files(paths: &[String]) -> miette::Result<()> {
+	let (mut errors, mut warnings) = (0, 0);
+	for path in formatter::collect_files(paths)? {
+		let name = path.display().to_string();
+		let source = read_to_string(&path).into_diagnostic()?;
+		let findings = match checker::check(&source, &name) {
+			Ok(findings) => findings,
+			Err(report) => vec![report],
+		};
+		for report in &findings {
+			eprintln!("{:?}", report);
+		}
+		let failed = findings.iter().filter(|x| checker::has_errors(std::slice::from_ref(x))).count();
+		errors += failed;
+		warnings += findings.len() - failed;
+	}
+	println!("{errors} hata, {warnings} uyarı bulundu.");
+	if errors > 0 {
+		return Err(miette::miette!("Denetim başarısız oldu."));
+	}
+	Ok(())
+}
+
+/// Rewrites `paths` in place, or with `check` only lists the files that are not formatted yet.
+fn format_files(paths: &[String], check: bool) -> miette::Result<()> {
+	let mut failed = 0;
+	for path in formatter::collect_files(paths)? {
+		let name = path.display().to_string();
+		let source = read_to_string(&path).into_diagnostic()?;
+		let formatted = match formatter::format(&source, &name) {
+			Ok(formatted) => formatted,
+			Err(report) => {
+				eprintln!("{:?}", report);
+				failed += 1;
+				continue;
+			}
+		};
+		if formatted == source {
+			continue;
+		}
+		if check {
+			let line = source.lines().zip(formatted.lines()).take_while(|(a, b)| a == b).count() + 1;
+			println!("{name}:{line}: biçimlendirilmemiş");
+			failed += 1;
+		} else {
+			std::fs::write(&path, formatted).into_diagnostic()?;
+			println!("{name} biçimlendirildi.");
+		}
+	}
+	if failed > 0 {
+		return Err(miette::miette!("{failed} dosya denetimden geçemedi."));
+	}
+	Ok(())
+}
+
 fn main() -> miette::Result<()> {
 	let cli = Cli::parse();
 	ctrlc::set_handler(|| {
@@ -126,19 +280,49 @@ fn main() -> miette::Result<()> {
         );
     });
 
-	match cli.command {
+	std::thread::Builder::new()
+		.stack_size(INTERPRETER_STACK_SIZE)
+		.spawn(move || run_command(cli.command))
+		.into_diagnostic()?
+		.join()
+		.unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+}
+
+fn run_command(command: Commands) -> miette::Result<()> {
+	match command {
 		Commands::Run {
 			file,
 			verbose,
 			printast,
 			printpreprocessoutput,
 			noexecute,
+			trace,
 			strict,
+			recursionlimit,
+			engine,
 		} => {
-			run_zen_file(file, verbose, printast, printpreprocessoutput, noexecute, strict)?;
+			run_zen_file(file, verbose, printast, printpreprocessoutput, noexecute, trace, strict, recursionlimit, engine)?;
+		}
+		Commands::Repl => {
+			util::repl::start(true)?;
+		}
+		Commands::Debug { file, breakpoints, strict, recursionlimit } => {
+			util::debugger::start(read_zen_file(&file), &file, breakpoints, strict, recursionlimit)?;
+		}
+		Commands::Dump { file, tokens, ast, format } => {
+			dump::dump(&read_zen_file(&file), &file, tokens, ast, format)?;
+		}
+		Commands::Check { files } => {
+			check_files(&files)?;
+		}
+		Commands::Lsp => {
+			lsp::start()?;
+		}
+		Commands::Fmt { files, check } => {
+			format_files(&files, check)?;
 		}
-		Commands::Test => {
-			run_tests();
+		Commands::Test { files, filter } => {
+			run_tests(&files, filter.as_deref())?;
 		}
 		Commands::Stats { write } => {
 			let readme_path = "README.md";
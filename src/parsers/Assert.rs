@@ -0,0 +1,12 @@
+use crate::features::tokenizer::{InstructionEnum, TokenData, TokenTable};
+use chumsky::prelude::*;
+
+use super::Parsers;
+
+pub fn parser() -> Box<dyn Parser<TokenData, InstructionEnum, Error = Simple<TokenData>>> {
+	let out = just(TokenTable::KeywordDoğrula.asTokenData())
+		.ignore_then(Parsers::value())
+		.map(InstructionEnum::Assert);
+
+	return Box::new(out);
+}
@@ -0,0 +1,14 @@
+use crate::features::tokenizer::{InstructionEnum, TokenData, TokenTable};
+use chumsky::prelude::*;
+
+use super::Parsers;
+
+pub fn parser() -> Box<dyn Parser<TokenData, InstructionEnum, Error = Simple<TokenData>>> {
+	let out = just(TokenTable::KeywordEşitOlmalı.asTokenData())
+		.ignore_then(Parsers::value())
+		.then_ignore(just(TokenTable::Comma.asTokenData()))
+		.then(Parsers::value())
+		.map(|(left, right)| InstructionEnum::AssertEqual(left, right));
+
+	return Box::new(out);
+}
@@ -0,0 +1,15 @@
+use crate::features::tokenizer::{InstructionEnum, TokenData, TokenTable};
+use chumsky::prelude::*;
+
+use super::Parsers;
+
+pub fn parser() -> Box<dyn Parser<TokenData, InstructionEnum, Error = Simple<TokenData>>> {
+	let out = just(TokenTable::KeywordYakala.asTokenData())
+		.ignore_then(Parsers::identifier().or_not())
+		.map(|name| InstructionEnum::CatchBlock {
+			name: name.map(|x| x.asIdentifier()),
+			scope_pointer: 0,
+		});
+
+	return Box::new(out);
+}
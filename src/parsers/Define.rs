@@ -13,7 +13,17 @@ pub fn parser() -> Box<dyn Parser<TokenData, InstructionEnum, Error = Simple<Tok
 	let out = filter(|x: &TokenData| x.token == TokenTable::Identifier)
 		.then(assignment_operator())
 		.then(Parsers::value())
-		.map(|((x, op), y)| InstructionEnum::VariableDeclaration(x.slice, y, op));
+		.map(|((x, op), y)| InstructionEnum::VariableDeclaration(x.slice, y, op))
+		.or(filter(|x: &TokenData| x.token == TokenTable::Identifier)
+			.then(Parsers::accessor().repeated().at_least(1))
+			.then(assignment_operator())
+			.then(Parsers::value())
+			.map(|(((x, path), method), value)| InstructionEnum::IndexAssignment {
+				name: x.slice,
+				path,
+				value,
+				method,
+			}));
 
 	return Box::new(out);
 }
@@ -0,0 +1,8 @@
+use crate::features::tokenizer::{InstructionEnum, TokenData, TokenTable};
+use chumsky::prelude::*;
+
+pub fn parser() -> Box<dyn Parser<TokenData, InstructionEnum, Error = Simple<TokenData>>> {
+	let out = just(TokenTable::KeywordSonunda.asTokenData()).map(|_| InstructionEnum::FinallyBlock { scope_pointer: 0 });
+
+	return Box::new(out);
+}
@@ -0,0 +1,22 @@
+use std::path::Path;
+
+use crate::features::tokenizer::{InstructionEnum, TokenData, TokenTable};
+use chumsky::prelude::*;
+
+use super::Parsers;
+
+pub fn parser() -> Box<dyn Parser<TokenData, InstructionEnum, Error = Simple<TokenData>>> {
+	let out = just(TokenTable::KeywordIçeAktar.asTokenData())
+		.ignore_then(Parsers::string())
+		.map(|path| {
+			let path = path.asStringLiteral();
+			// The module is named after its file: `içe aktar "lib/mat.zen"` -> `mat`
+			let namespace = Path::new(&path)
+				.file_stem()
+				.map(|x| x.to_string_lossy().into_owned())
+				.unwrap_or_else(|| path.clone());
+			InstructionEnum::Import { path, namespace }
+		});
+
+	return Box::new(out);
+}
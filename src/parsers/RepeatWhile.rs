@@ -0,0 +1,14 @@
+use super::Parsers;
+use crate::features::tokenizer::{InstructionEnum, TokenData, TokenTable};
+use chumsky::prelude::*;
+
+pub fn parser() -> Box<dyn Parser<TokenData, InstructionEnum, Error = Simple<TokenData>>> {
+	let out = Parsers::value()
+		.then_ignore(just(TokenTable::KeywordOlmadığıSüreceTekrarla.asTokenData()))
+		.map(|condition| InstructionEnum::RepeatWhile {
+			condition,
+			scope_pointer: 0,
+		});
+
+	return Box::new(out);
+}
@@ -0,0 +1,16 @@
+use crate::features::tokenizer::{InstructionEnum, TokenData, TokenTable};
+use chumsky::prelude::*;
+
+use super::Parsers;
+
+pub fn parser() -> Box<dyn Parser<TokenData, InstructionEnum, Error = Simple<TokenData>>> {
+	let out = just(TokenTable::KeywordTest.asTokenData())
+		.ignore_then(Parsers::string())
+		.then_ignore(just(TokenTable::Colon.asTokenData()).or_not())
+		.map(|name| InstructionEnum::Test {
+			name: name.asStringLiteral(),
+			scope_pointer: 0,
+		});
+
+	return Box::new(out);
+}
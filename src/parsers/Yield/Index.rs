@@ -8,10 +8,8 @@ use chumsky::prelude::*;
 
 pub fn parser() -> Box<dyn Parser<TokenData, YieldInstructionEnum, Error = Simple<TokenData>>> {
 	let out = filter(|x: &TokenData| x.token == TokenTable::Identifier)
-		.then_ignore(just(TokenTable::LCRBRACKET.asTokenData()))
-		.then(Parsers::atomic())
-		.then_ignore(just(TokenTable::RCRBRACKET.asTokenData()))
-		.map(|(x, y)| YieldInstructionEnum::Index(x.asIdentifier(), Box::new(y.into())));
+		.then(Parsers::accessor().repeated().at_least(1))
+		.map(|(x, path)| YieldInstructionEnum::Index(x.asIdentifier(), path));
 
 	return Box::new(out);
 }
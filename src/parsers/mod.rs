@@ -1,31 +1,42 @@
 #![allow(dead_code)]
 
+pub mod Assert;
+pub mod AssertEqual;
 pub mod Break;
+pub mod Catch;
 pub mod Continue;
 pub mod Define;
 pub mod Elif;
 pub mod Else;
 pub mod For;
+pub mod Finally;
 pub mod ForIn;
 pub mod Function;
 pub mod FunctionCall;
 pub mod Yield;
 use Yield::*;
 pub mod If;
+pub mod Import;
 pub mod Print;
 pub mod Repeat;
+pub mod RepeatUntil;
+pub mod RepeatWhile;
 pub mod Return;
+pub mod Test;
+pub mod Throw;
+pub mod Try;
 pub mod Type;
 pub mod Wait;
 pub mod WhileTrue;
 
 pub mod Parsers {
 	use super::{
-		Break, Continue, Define, Elif, Else, For, ForIn, Function, FunctionCall, FunctionCallYield, If, Input, Print, Random, Repeat, Return, Type,
-		Wait, WhileTrue, Index
+		Assert, AssertEqual, Break, Catch, Continue, Define, Elif, Else, Finally, For, ForIn, Function, FunctionCall, FunctionCallYield, If, Import, Input, Print, Random, Repeat,
+		RepeatUntil, RepeatWhile, Return, Test, Throw, Try, Type, Wait, WhileTrue, Index
 	};
-	use crate::features::tokenizer::{AssignmentMethod, Atom, InstructionEnum, TokenData, TokenTable, YieldInstructionEnum};
-	use crate::library::Types::{Object, ParameterData, RandomizerType};
+	use crate::features::tokenizer::{Accessor, AssignmentMethod, Atom, InstructionEnum, Span, Statement, TokenData, TokenTable, YieldInstructionEnum};
+	use crate::library::Error::{DegiskenBulunamadı, TipHatası};
+	use crate::library::Types::{Map, Object, ParameterData, RandomizerType};
 	use crate::util::ScopeManager::ScopeManager;
 	use chumsky::prelude::*;
 	use num::pow::Pow;
@@ -52,27 +63,98 @@ pub mod Parsers {
 		Box::new(inp.map(|x| (ParserOutput { indent: false }, x)))
 	}
 
+	/// A single instruction, without requiring the input to end after it.
+	pub fn instruction() -> ParserType2 {
+		Box::new(choice([
+			WithIndentation(Repeat::parser()),
+			WithIndentation(If::parser()),
+			WithIndentation(Elif::parser()),
+			WithIndentation(Else::parser()),
+			WithIndentation(WhileTrue::parser()),
+			WithIndentation(RepeatUntil::parser()),
+			WithIndentation(RepeatWhile::parser()),
+			WithIndentation(Function::parser()),
+			WithIndentation(For::parser()),
+			WithIndentation(ForIn::parser()),
+			WithIndentation(Try::parser()),
+			WithIndentation(Catch::parser()),
+			WithIndentation(Finally::parser()),
+			WithIndentation(Test::parser()),
+			WithoutIndentation(FunctionCall::parser()),
+			WithoutIndentation(Print::parser()),
+			WithoutIndentation(Define::parser()),
+			WithoutIndentation(Break::parser()),
+			WithoutIndentation(Continue::parser()),
+			WithoutIndentation(Return::parser()),
+			WithoutIndentation(Type::parser()),
+			WithoutIndentation(Wait::parser()),
+			WithoutIndentation(Import::parser()),
+			WithoutIndentation(Throw::parser()),
+			WithoutIndentation(Assert::parser()),
+			WithoutIndentation(AssertEqual::parser()),
+		]))
+	}
+
 	pub fn parser() -> Box<dyn Parser<TokenData, (ParserOutput, InstructionEnum), Error = Simple<TokenData>>> {
-		Box::new(recursive(|instr_parser| {
-			choice([
-				WithIndentation(Repeat::parser()),
-				WithIndentation(If::parser()),
-				WithIndentation(Elif::parser()),
-				WithIndentation(Else::parser()),
-				WithIndentation(WhileTrue::parser()),
-				WithIndentation(Function::parser()),
-				WithIndentation(For::parser()),
-				WithIndentation(ForIn::parser()),
-				WithoutIndentation(FunctionCall::parser()),
-				WithoutIndentation(Print::parser()),
-				WithoutIndentation(Define::parser()),
-				WithoutIndentation(Break::parser()),
-				WithoutIndentation(Continue::parser()),
-				WithoutIndentation(Return::parser()),
-				WithoutIndentation(Type::parser()),
-				WithoutIndentation(Wait::parser()),
-			])
-		}))
+		// Every instruction has to use up the whole line, otherwise leftovers like `yazdır (1 +` would be ignored.
+		Box::new(instruction().then_ignore(end()))
+	}
+
+	/// A whole file laid out by `tokenize_file`: each statement is a line ending in `Newline`, block headers are
+	/// followed by their body between `Indent` and `Dedent`. Lines indented under anything else are rejected.
+	pub fn program() -> Box<dyn Parser<TokenData, Vec<Statement>, Error = Simple<TokenData>>> {
+		let statement = recursive(|statement| {
+			// Only the first token is looked at ahead of time, so errors inside the line are reported where they are.
+			let header = filter(|x: &TokenData| !matches!(x.token, TokenTable::Newline | TokenTable::Indent | TokenTable::Dedent))
+				.rewind()
+				.then(instruction())
+				.then(token(TokenTable::Newline));
+			let body = token(TokenTable::Indent)
+				.map_with_span(|_, span| span)
+				.then(statement.repeated().at_least(1))
+				.then_ignore(token(TokenTable::Dedent));
+
+			header.then(body.or_not()).validate(|(((first, (output, instr)), newline), body), _, emit| {
+				let body = match body {
+					// The body was still parsed, so mistakes inside it are reported as well.
+					Some((indent, _)) if !output.indent => {
+						emit(Simple::custom(indent, "Beklenmeyen girinti"));
+						vec![]
+					}
+					body => body.map(|(_, body)| body).unwrap_or_default(),
+				};
+				Statement {
+					instr,
+					// `Newline` sits right after the last token of the line.
+					span: first.span.to(newline.span),
+					body,
+				}
+			})
+		});
+		Box::new(statement.repeated().then_ignore(end()))
+	}
+
+	thread_local! {
+		// Building the combinator graph costs far more than running it on a line, so it is built once per thread.
+		// Chumsky's recursive parsers are `Rc` based, which rules out a process wide `static`.
+		static LINE_PARSER: ParserType2 = parser();
+		static PROGRAM_PARSER: Box<dyn Parser<TokenData, Vec<Statement>, Error = Simple<TokenData>>> = program();
+		static EXPRESSION_PARSER: Box<dyn Parser<TokenData, Atom, Error = Simple<TokenData>>> = Box::new(value().then_ignore(end()));
+	}
+
+	/// Parses one line of tokens (without tabs) with the cached `parser()`.
+	pub fn parse_line(line_feed: Vec<TokenData>) -> Result<(ParserOutput, InstructionEnum), Vec<Simple<TokenData>>> {
+		LINE_PARSER.with(|parser| parser.parse(line_feed))
+	}
+
+	/// Parses a whole file laid out by `tokenize_file` with the cached `program()`.
+	pub fn parse_program(tokens: Vec<TokenData>) -> Result<Vec<Statement>, Vec<Simple<TokenData>>> {
+		PROGRAM_PARSER.with(|parser| parser.parse(tokens))
+	}
+
+	/// Parses tokens that have to make up a single value, with a cached `value()`.
+	pub fn parse_value(line_feed: Vec<TokenData>) -> Result<Atom, Vec<Simple<TokenData>>> {
+		EXPRESSION_PARSER.with(|parser| parser.parse(line_feed))
 	}
 
 	pub fn yield_instruction_parser() -> Box<dyn Parser<TokenData, YieldInstructionEnum, Error = Simple<TokenData>>> {
@@ -106,6 +188,20 @@ pub mod Parsers {
 		GreaterThanOrEqual(Box<Expression>, Box<Expression>),
 		Equal(Box<Expression>, Box<Expression>),
 		NotEqual(Box<Expression>, Box<Expression>),
+		/// `öğe içinde değer`: whether a map has the key, an array has the element or a text has the piece of text.
+		In(Box<Expression>, Box<Expression>),
+
+		/// `{anahtar: değer, ...}` literal, evaluated into an `Object::Map`.
+		Map(Vec<(Expression, Expression)>),
+
+		And(Box<Expression>, Box<Expression>),
+		Or(Box<Expression>, Box<Expression>),
+
+		/// `"Merhaba {ad}"`, the evaluated parts are joined into one text.
+		Format(Vec<Expression>),
+
+		/// Marks where the inner expression was written, errors raised while evaluating it point there.
+		Spanned(Box<Expression>, Span),
 	}
 
 	impl From<f64> for Expression {
@@ -142,69 +238,128 @@ pub mod Parsers {
 			Self::Value(Box::new(false.into()))
 		}
 
-		pub fn evaluate(&self, currentScope: usize, manager: &mut ScopeManager) -> Object {
+		pub fn with_span(self, span: Option<Span>) -> Self {
+			match span {
+				Some(span) => Self::Spanned(Box::new(self), span),
+				None => self,
+			}
+		}
+
+		pub fn span(&self) -> Option<Span> {
 			match self {
+				Self::Spanned(_, span) => Some(*span),
+				_ => None,
+			}
+		}
+
+		pub fn evaluate(&self, currentScope: usize, manager: &mut ScopeManager) -> miette::Result<Object> {
+			Ok(match self {
+				Expression::Spanned(inner, span) => {
+					let outer = manager.span();
+					manager.set_location(*span);
+					let value = inner.evaluate(currentScope, manager)?;
+					manager.set_location(outer);
+					value
+				}
+				Expression::Format(parts) => {
+					let mut text = String::new();
+					for part in parts {
+						text.push_str(&part.evaluate(currentScope, manager)?.to_string());
+					}
+					Object::from(text)
+				}
 				Expression::Not(inner) => {
-					let inner_value = inner.evaluate(currentScope, manager);
+					let inner_value = inner.evaluate(currentScope, manager)?;
 					Object::from(!inner_value.isTruthy())
 				}
 				Expression::Value(val) => {
 					if let Object::Variable(name) = *val.clone() {
-						manager
-							.get_var(currentScope, name.clone())
-							.unwrap_or_else(|| panic!("Variable {} not found in scope {}", name, currentScope))
+						match manager.get_var(currentScope, &name) {
+							Some(value) => value,
+							None => {
+								return Err(DegiskenBulunamadı {
+									src: manager.source(),
+									bad_bit: manager.location(),
+								})?;
+							}
+						}
 					} else {
 						*val.clone()
 					}
 				}
 				Expression::Add(lhs, rhs) => {
-					let left = lhs.evaluate(currentScope, manager);
-					let right = rhs.evaluate(currentScope, manager);
+					let left = lhs.evaluate(currentScope, manager)?;
+					let right = rhs.evaluate(currentScope, manager)?;
 					left + right
 				}
 				Expression::Sub(lhs, rhs) => {
-					let left = lhs.evaluate(currentScope, manager);
-					let right = rhs.evaluate(currentScope, manager);
+					let left = lhs.evaluate(currentScope, manager)?;
+					let right = rhs.evaluate(currentScope, manager)?;
 					left - right
 				}
 				Expression::Mul(lhs, rhs) => {
-					let left = lhs.evaluate(currentScope, manager);
-					let right = rhs.evaluate(currentScope, manager);
+					let left = lhs.evaluate(currentScope, manager)?;
+					let right = rhs.evaluate(currentScope, manager)?;
 					left * right
 				}
 				Expression::Div(lhs, rhs) => {
-					let left = lhs.evaluate(currentScope, manager);
-					let right = rhs.evaluate(currentScope, manager);
+					let left = lhs.evaluate(currentScope, manager)?;
+					let right = rhs.evaluate(currentScope, manager)?;
 					left / right
 				}
 				Expression::Mod(lhs, rhs) => {
-					let left = lhs.evaluate(currentScope, manager);
-					let right = rhs.evaluate(currentScope, manager);
+					let left = lhs.evaluate(currentScope, manager)?;
+					let right = rhs.evaluate(currentScope, manager)?;
 					left % right
 				}
 				Expression::Pow(lhs, rhs) => {
-					let left = lhs.evaluate(currentScope, manager);
-					let right = rhs.evaluate(currentScope, manager);
+					let left = lhs.evaluate(currentScope, manager)?;
+					let right = rhs.evaluate(currentScope, manager)?;
 					left.pow(right)
 				}
-				Expression::LessThan(lhs, rhs) => Object::from(lhs.evaluate(currentScope, manager) < rhs.evaluate(currentScope, manager)),
-				Expression::GreaterThan(lhs, rhs) => Object::from(lhs.evaluate(currentScope, manager) > rhs.evaluate(currentScope, manager)),
-				Expression::LessThanOrEqual(lhs, rhs) => Object::from(lhs.evaluate(currentScope, manager) <= rhs.evaluate(currentScope, manager)),
-				Expression::GreaterThanOrEqual(lhs, rhs) => Object::from(lhs.evaluate(currentScope, manager) >= rhs.evaluate(currentScope, manager)),
+				Expression::Map(entries) => {
+					let mut map = Map { value: vec![] };
+					for (key_expr, value) in entries {
+						let key = key_expr.evaluate(currentScope, manager)?;
+						if !Map::is_valid_key(&key) {
+							return Err(TipHatası::expected(
+								"Sözlük anahtarlarının bir Sayı veya Metin olması bekleniyordu.".to_owned(),
+								format!("{:?}", key),
+								manager.source(),
+								key_expr.span().map(Into::into).unwrap_or(manager.location()),
+							))?;
+						}
+						let value = value.evaluate(currentScope, manager)?;
+						map.insert(key, value);
+					}
+					Object::Map(map)
+				}
+				Expression::LessThan(lhs, rhs) => Object::from(lhs.evaluate(currentScope, manager)? < rhs.evaluate(currentScope, manager)?),
+				Expression::GreaterThan(lhs, rhs) => Object::from(lhs.evaluate(currentScope, manager)? > rhs.evaluate(currentScope, manager)?),
+				Expression::LessThanOrEqual(lhs, rhs) => Object::from(lhs.evaluate(currentScope, manager)? <= rhs.evaluate(currentScope, manager)?),
+				Expression::GreaterThanOrEqual(lhs, rhs) => Object::from(lhs.evaluate(currentScope, manager)? >= rhs.evaluate(currentScope, manager)?),
 				Expression::Equal(lhs, rhs) => {
-					let left = lhs.evaluate(currentScope, manager);
-					let right = rhs.evaluate(currentScope, manager);
+					let left = lhs.evaluate(currentScope, manager)?;
+					let right = rhs.evaluate(currentScope, manager)?;
 					(left == right).into()
 				}
 				Expression::NotEqual(lhs, rhs) => {
-					let left = lhs.evaluate(currentScope, manager);
-					let right = rhs.evaluate(currentScope, manager);
+					let left = lhs.evaluate(currentScope, manager)?;
+					let right = rhs.evaluate(currentScope, manager)?;
 					(left != right).into()
 				}
-			}
+				Expression::In(lhs, rhs) => {
+					let item = lhs.evaluate(currentScope, manager)?;
+					let container = rhs.evaluate(currentScope, manager)?;
+					container.contains(&item, manager.source(), manager.location())?.into()
+				}
+				// Short-circuiting: the right hand side is only evaluated when it can change the result.
+				Expression::And(lhs, rhs) => Object::from(lhs.isTruthy(currentScope, manager)? && rhs.isTruthy(currentScope, manager)?),
+				Expression::Or(lhs, rhs) => Object::from(lhs.isTruthy(currentScope, manager)? || rhs.isTruthy(currentScope, manager)?),
+			})
 		}
-		pub fn isTruthy(&self, currentScope: usize, manager: &mut ScopeManager) -> bool {
-			self.evaluate(currentScope, manager).isTruthy()
+		pub fn isTruthy(&self, currentScope: usize, manager: &mut ScopeManager) -> miette::Result<bool> {
+			Ok(self.evaluate(currentScope, manager)?.isTruthy())
 		}
 	}
 
@@ -224,7 +379,26 @@ pub mod Parsers {
 				Expression::GreaterThanOrEqual(lhs, rhs) => write!(f, "({} >= {})", lhs, rhs),
 				Expression::Equal(lhs, rhs) => write!(f, "({} == {})", lhs, rhs),
 				Expression::NotEqual(lhs, rhs) => write!(f, "({} != {})", lhs, rhs),
+				Expression::In(lhs, rhs) => write!(f, "({} içinde {})", lhs, rhs),
+				Expression::And(lhs, rhs) => write!(f, "({} ve {})", lhs, rhs),
+				Expression::Or(lhs, rhs) => write!(f, "({} veya {})", lhs, rhs),
 				Expression::Not(inner) => write!(f, "(!{})", inner),
+				Expression::Spanned(inner, _) => write!(f, "{}", inner),
+				Expression::Format(parts) => {
+					write!(f, "\"")?;
+					for part in parts {
+						match part {
+							// Text pieces are the only bare values, everything else came from inside braces.
+							Expression::Value(text) => write!(f, "{}", text)?,
+							other => write!(f, "{{{}}}", other)?,
+						}
+					}
+					write!(f, "\"")
+				}
+				Expression::Map(entries) => {
+					let entries = entries.iter().map(|(k, v)| format!("{}: {}", k, v)).collect::<Vec<_>>();
+					write!(f, "{{{}}}", entries.join(", "))
+				}
 			}
 		}
 	}
@@ -233,71 +407,123 @@ pub mod Parsers {
 		Box::new(
 			recursive(|parser| {
 				filter(|x: &TokenData| {
-					x.token == TokenTable::StringLiteral
-						|| x.token == TokenTable::NumberLiteral
-						|| x.token == TokenTable::BooleanLiteral
-						|| x.token == TokenTable::Identifier
+					x.token == TokenTable::StringLiteral || x.token == TokenTable::NumberLiteral || x.token == TokenTable::BooleanLiteral
 				})
-				.map(|x| x.asObject())
-				.or(just(TokenTable::LSQBRACKET.asTokenData())
-					.ignore_then(parser.clone().separated_by(just(TokenTable::Comma.asTokenData())))
-					.then_ignore(just(TokenTable::RSQBRACKET.asTokenData()))
-					.map(|exprs| Object::from(exprs)))
+				.map(|x| (x.asObject(), x.span))
+				.or(identifier()
+					.then(just(TokenTable::Dot.asTokenData()).ignore_then(identifier()).repeated())
+					.map(|(first, rest)| {
+						let span = rest.last().map_or(first.span, |last| first.span.to(last.span));
+						let name = std::iter::once(&first).chain(&rest).map(|x| x.asIdentifier()).collect::<Vec<_>>();
+						(Object::Variable(name.join(".")), span)
+					}))
+				.or(token(TokenTable::LSQBRACKET)
+					.then(parser.clone().separated_by(just(TokenTable::Comma.asTokenData())))
+					.then(token(TokenTable::RSQBRACKET))
+					.map(|((open, items), close): ((TokenData, Vec<(Object, Span)>), TokenData)| {
+						(Object::from(items.into_iter().map(|(x, _)| x).collect::<Vec<_>>()), open.span.to(close.span))
+					}))
 			})
-			.map(|x| x.into()),
+			.map(|(x, span)| Expression::from(x).with_span(Some(span))),
 		)
 	}
 
+	/// Applies the binary operator `op`, the result spans from the start of `lhs` to the end of `rhs`.
+	fn binary(lhs: Expression, op: TokenData, rhs: Expression) -> Expression {
+		let span = match (lhs.span(), rhs.span()) {
+			(Some(lhs), Some(rhs)) => Some(lhs.to(rhs)),
+			(lhs, rhs) => lhs.or(rhs),
+		};
+		op.toOp()(Box::new(lhs), Box::new(rhs)).with_span(span)
+	}
+
 	// FIXME: Fix parantheses support.
 	pub fn atomic() -> impl Parser<TokenData, Expression, Error = Simple<TokenData>> {
 		let (paren_left, paren_right) = parens();
 
 		let expr = recursive(|expr| {
-			let not_operator = just(TokenTable::ExclamationMark.asTokenData());
-
-			let atom = Rc::new(
-				not_operator
-					.then(expr.clone())
-					.map(|(_, inner)| Expression::Not(Box::new(inner)))
-					.or(just(TokenTable::MathOperatorSubtract.asTokenData())
-						.then(object())
-						.map(|(_, obj)| Expression::Sub(Box::new(Expression::from(0f64)), Box::new(obj))))
-					.or(object())
-					.or(expr.delimited_by(paren_left.clone(), paren_right.clone())),
-			);
-
-			let mul_operator = just(TokenTable::MathOperatorMultiply.asTokenData())
-				.or(just(TokenTable::MathOperatorPower.asTokenData()))
-				.or(just(TokenTable::MathOperatorDivide.asTokenData()))
-				.or(just(TokenTable::MathOperatorMod.asTokenData()));
-
-			let add_operator = just(TokenTable::MathOperatorAdd.asTokenData()).or(just(TokenTable::MathOperatorSubtract.asTokenData()));
-
-			let comparison_operator = choice([
-				just(TokenTable::ComparisonOperatorEqual.asTokenData()),
-				just(TokenTable::ComparisonOperatorNotEqual.asTokenData()),
-				just(TokenTable::ComparisonOperatorGreaterThan.asTokenData()),
-				just(TokenTable::ComparisonOperatorGreaterThanOrEqual.asTokenData()),
-				just(TokenTable::ComparisonOperatorLessThan.asTokenData()),
-				just(TokenTable::ComparisonOperatorLessThanOrEqual.asTokenData()),
-			]);
-
-			let mul = atom
-				.clone()
-				.then(mul_operator.clone().then(atom.clone()).repeated())
-				.foldl(|lhs, (op, rhs)| op.toOp()(Box::new(lhs), Box::new(rhs)));
-
-			let add = mul
+			// `!` / `değil` negate a whole comparison, so they sit between comparisons and `ve` / `veya`.
+			let comparison = recursive(|comparison| {
+				let not_operator = token(TokenTable::ExclamationMark).or(token(TokenTable::KeywordDeğil));
+
+				let atom = Rc::new(
+					not_operator
+						.then(comparison.clone())
+						.map(|(op, inner): (TokenData, Expression)| {
+							let span = inner.span().map(|x| op.span.to(x));
+							Expression::Not(Box::new(inner)).with_span(span)
+						})
+						.or(token(TokenTable::MathOperatorSubtract)
+							.then(object())
+							.map(|(op, obj)| {
+								let span = obj.span().map(|x| op.span.to(x));
+								Expression::Sub(Box::new(Expression::from(0f64)), Box::new(obj)).with_span(span)
+							}))
+						.or(token(TokenTable::InterpolationStart)
+							.then(
+								string()
+									.map(|x| Expression::from(x.asObject()))
+									.or(expr
+										.clone()
+										.delimited_by(just(TokenTable::LCRBRACKET.asTokenData()), just(TokenTable::RCRBRACKET.asTokenData())))
+									.repeated(),
+							)
+							.then(token(TokenTable::InterpolationEnd))
+							.map(|((open, parts), close)| Expression::Format(parts).with_span(Some(open.span.to(close.span)))))
+						.or(object())
+						.or(token(TokenTable::LCRBRACKET)
+							.then(
+								expr.clone()
+									.then_ignore(just(TokenTable::Colon.asTokenData()))
+									.then(expr.clone())
+									.separated_by(just(TokenTable::Comma.asTokenData()))
+									.allow_trailing(),
+							)
+							.then(token(TokenTable::RCRBRACKET))
+							.map(|((open, entries), close)| Expression::Map(entries).with_span(Some(open.span.to(close.span)))))
+						.or(expr.clone().delimited_by(paren_left.clone(), paren_right.clone())),
+				);
+
+				let mul_operator = just(TokenTable::MathOperatorMultiply.asTokenData())
+					.or(just(TokenTable::MathOperatorPower.asTokenData()))
+					.or(just(TokenTable::MathOperatorDivide.asTokenData()))
+					.or(just(TokenTable::MathOperatorMod.asTokenData()));
+
+				let add_operator = just(TokenTable::MathOperatorAdd.asTokenData()).or(just(TokenTable::MathOperatorSubtract.asTokenData()));
+
+				let comparison_operator = choice([
+					just(TokenTable::ComparisonOperatorEqual.asTokenData()),
+					just(TokenTable::ComparisonOperatorNotEqual.asTokenData()),
+					just(TokenTable::ComparisonOperatorGreaterThan.asTokenData()),
+					just(TokenTable::ComparisonOperatorGreaterThanOrEqual.asTokenData()),
+					just(TokenTable::ComparisonOperatorLessThan.asTokenData()),
+					just(TokenTable::ComparisonOperatorLessThanOrEqual.asTokenData()),
+					just(TokenTable::Keywordİçinde.asTokenData()),
+				]);
+
+				let mul = atom
+					.clone()
+					.then(mul_operator.clone().then(atom.clone()).repeated())
+					.foldl(|lhs, (op, rhs)| binary(lhs, op, rhs));
+
+				let add = mul
+					.clone()
+					.then(add_operator.clone().then(mul.clone()).repeated())
+					.foldl(|lhs, (op, rhs)| binary(lhs, op, rhs));
+
+				add.clone()
+					.then(comparison_operator.then(add.clone()).repeated())
+					.foldl(|lhs, (op, rhs)| binary(lhs, op, rhs))
+			});
+
+			let and = comparison
 				.clone()
-				.then(add_operator.clone().then(mul.clone()).repeated())
-				.foldl(|lhs, (op, rhs)| op.toOp()(Box::new(lhs), Box::new(rhs)));
+				.then(just(TokenTable::KeywordVe.asTokenData()).then(comparison.clone()).repeated())
+				.foldl(|lhs, (op, rhs)| binary(lhs, op, rhs));
 
-			let comparison = add
-				.clone()
-				.then(comparison_operator.then(add.clone()).repeated())
-				.foldl(|lhs, (op, rhs)| op.toOp()(Box::new(lhs), Box::new(rhs)));
-
-			comparison
+			and.clone()
+				.then(just(TokenTable::KeywordVeya.asTokenData()).then(and.clone()).repeated())
+				.foldl(|lhs, (op, rhs)| binary(lhs, op, rhs))
 		});
 
 		expr
@@ -324,6 +550,24 @@ pub mod Parsers {
 		)
 	}
 
+	/// `{i}` or `{başlangıç:bitiş}` with either bound optional.
+	pub fn accessor() -> Box<dyn Parser<TokenData, Accessor, Error = Simple<TokenData>>> {
+		Box::new(
+			atomic()
+				.or_not()
+				.then_ignore(just(TokenTable::Colon.asTokenData()))
+				.then(atomic().or_not())
+				.map(|(from, to)| Accessor::Slice(from, to))
+				.or(atomic().map(Accessor::Index))
+				.delimited_by(just(TokenTable::LCRBRACKET.asTokenData()), just(TokenTable::RCRBRACKET.asTokenData())),
+		)
+	}
+
+	/// Like `just`, but outputs the token from the input so its span can be used.
+	pub fn token(kind: TokenTable) -> impl Parser<TokenData, TokenData, Error = Simple<TokenData>> + Clone {
+		filter(move |x: &TokenData| x.token == kind)
+	}
+
 	pub fn number() -> Box<dyn Parser<TokenData, TokenData, Error = Simple<TokenData>>> {
 		Box::new(filter(|x: &TokenData| x.token == TokenTable::NumberLiteral))
 	}
@@ -340,6 +584,16 @@ pub mod Parsers {
 		Box::new(filter(|x: &TokenData| x.token == TokenTable::Identifier))
 	}
 
+	/// A name that can reach into imported modules, e.g. `mat.kare`.
+	pub fn qualified_identifier() -> Box<dyn Parser<TokenData, String, Error = Simple<TokenData>>> {
+		Box::new(
+			identifier()
+				.separated_by(just(TokenTable::Dot.asTokenData()))
+				.at_least(1)
+				.map(|parts| parts.iter().map(|x| x.asIdentifier()).collect::<Vec<_>>().join(".")),
+		)
+	}
+
 	pub fn main_types() -> Box<dyn Parser<TokenData, TokenData, Error = Simple<TokenData>>> {
 		Box::new(
 			just(TokenTable::KeywordMetin.asTokenData())
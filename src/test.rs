@@ -1,11 +1,122 @@
-use crate::util::ScopeManager::ScopeManager;
+//! `zen test`: runs the `test "açıklama":` blocks of Zen files. Every test gets a fresh `ScopeManager`, the top
+//! level of its file runs first so the test sees the functions and variables the file declares, then its body runs.
+//! What they print is only shown when the test fails.
 
-pub fn run_tests() {
+use std::{
+	fs::read_to_string,
+	io::Write,
+	panic::{AssertUnwindSafe, catch_unwind},
+};
+
+use colored::Colorize;
+use miette::{IntoDiagnostic, NamedSource, SourceSpan, miette};
+
+use crate::{
+	features::{
+		formatter,
+		tokenizer::{InstructionEnum, Statement},
+	},
+	util::{
+		ScopeManager::ScopeManager,
+		process::{BuildScopes, DeclareGlobals, ExecuteBlock, ParseSource},
+	},
+};
+
+/// Runs the tests in the `.zen` files under `paths`. With `filter` only the tests whose name contains it run.
+pub fn run_tests(paths: &[String], filter: Option<&str>) -> miette::Result<()> {
+	run_tests_to(paths, filter, &mut std::io::stdout().lock())
+}
+
+/// `run_tests`, writing the results to `out`.
+pub fn run_tests_to(paths: &[String], filter: Option<&str>, out: &mut dyn Write) -> miette::Result<()> {
+	let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+	for path in formatter::collect_files(paths)? {
+		let filename = path.display().to_string();
+		let source = read_to_string(&path).into_diagnostic()?;
+		let statements = match ParseSource(&source, &filename, 0, true) {
+			Ok(statements) => statements,
+			Err(report) => {
+				writeln!(out, "{} {filename}\n{:?}", "✗".red(), report).into_diagnostic()?;
+				failed += 1;
+				continue;
+			}
+		};
+
+		for (index, name) in tests(&statements).into_iter().enumerate() {
+			if filter.is_some_and(|filter| !name.contains(filter)) {
+				skipped += 1;
+				continue;
+			}
+			let (result, output) = RunTest(&source, &filename, &statements, index);
+			match result {
+				Ok(()) => {
+					writeln!(out, "{} {filename}: {name}", "✓".green()).into_diagnostic()?;
+					passed += 1;
+				}
+				Err(report) => {
+					writeln!(out, "{} {filename}: {name}\n{output}{:?}", "✗".red(), report).into_diagnostic()?;
+					failed += 1;
+				}
+			}
+		}
+	}
+
+	if passed + failed + skipped == 0 {
+		writeln!(out, "Hiç test bulunamadı.").into_diagnostic()?;
+	}
+	writeln!(out, "{passed} başarılı, {failed} başarısız, {skipped} atlandı.").into_diagnostic()?;
+	if failed > 0 {
+		return Err(miette!("{failed} test başarısız oldu."));
+	}
+	Ok(())
+}
+
+/// Names of the tests at the top level of a file, in the order they are written. Tests inside other blocks never run.
+fn tests(statements: &[Statement]) -> Vec<&str> {
+	statements
+		.iter()
+		.filter_map(|x| match &x.instr {
+			InstructionEnum::Test { name, .. } => Some(name.as_str()),
+			_ => None,
+		})
+		.collect()
+}
+
+/// Runs the `index`th test of the file on a manager of its own, returning the outcome and what the file and the
+/// test printed. `statements` were parsed as file 0.
+fn RunTest(source: &str, filename: &str, statements: &[Statement], index: usize) -> (miette::Result<()>, String) {
 	let mut manager = ScopeManager::new();
+	manager.set_strict(true);
+	let output = manager.capture_output();
 	let root_scope = manager.create_scope(None, None);
-	let child_scope = manager.create_scope(Some(0), None);
-	// manager.define_var(scope_id, name, value);
+	if let Ok(path) = std::path::Path::new(filename).canonicalize() {
+		manager.push_import(path);
+	}
+	manager.add_source(filename, source.to_owned());
+	BuildScopes(statements, root_scope, &mut manager);
+	DeclareGlobals(root_scope, &mut manager);
+
+	let test_scope = manager
+		.get_scope(root_scope)
+		.and_then(|scope| {
+			scope
+				.block
+				.iter()
+				.filter_map(|(instr, _)| match instr {
+					InstructionEnum::Test { scope_pointer, .. } => Some(*scope_pointer),
+					_ => None,
+				})
+				.nth(index)
+		})
+		.expect("BuildScopes keeps every top level test");
 
-	println!("{:#?}", manager.get_scope(root_scope));
-	println!("{:#?}", manager.get_scope(child_scope));
+	let src = NamedSource::new(filename, source.to_owned());
+	let span = SourceSpan::new(0.into(), source.len());
+	let result = catch_unwind(AssertUnwindSafe(|| {
+		ExecuteBlock(root_scope, &mut manager, src.clone(), span)?;
+		ExecuteBlock(test_scope, &mut manager, src.clone(), span).map(|_| ())
+	}))
+	.unwrap_or_else(|_| Err(miette!("Test çalışırken yorumlayıcı beklenmedik bir şekilde durdu.")));
+	let output = output.borrow().clone();
+	(result, output)
 }
@@ -1,13 +1,16 @@
 use std::{
+	cell::RefCell,
 	collections::{HashMap, HashSet},
+	rc::Rc,
+	path::{Path, PathBuf},
 	fmt::{Display, write},
 	thread::scope,
 };
 
 use crate::{
-	features::tokenizer::{Atom, InstructionEnum, TokenData},
+	features::tokenizer::{Atom, InstructionEnum, Span, TokenData},
 	library::{
-		Error::{CokFazlaArguman, EksikArguman, FonksiyonBulunamadı},
+		Error::{CokFazlaArguman, EksikArguman, FonksiyonBulunamadı, OzyinelemeHatası, YiginTukendi},
 		Types::{Boolean, Function, Object, ParameterData, ResolvedParameterData},
 	},
 	parsers::Parsers::Expression,
@@ -20,8 +23,14 @@ pub enum ScopeAction {
 	For(Atom, Atom, Option<Atom>, String),
 	ForIn{ name: String, step: Option<Atom> },
 	WhileTrue,
+	RepeatUntil(Atom),
+	RepeatWhile(Atom),
 	Condition(Atom),
 	Function { name: String, args: Vec<ParameterData> },
+	Try,
+	Catch(Option<String>),
+	Finally,
+	Test(String),
 }
 
 impl Display for ScopeAction {
@@ -57,19 +66,21 @@ use super::process::{BlockOutput, ExecuteBlock};
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConditionStructure {
 	pub scope_pointer: usize,
-	pub condition: Expression,
+	/// Resolved each time the condition is checked, not when the scope tree is built.
+	pub condition: Atom,
 }
 
 impl ConditionStructure {
 	pub fn empty() -> Self {
 		Self {
-			condition: Expression::falsy(),
+			condition: Expression::falsy().into(),
 			scope_pointer: 0,
 		}
 	}
 
 	pub fn is_empty(&self) -> bool {
-		matches!(self.condition, Expression::Value(ref obj) if matches!(**obj, Object::Bool(Boolean { value: false }))) && self.scope_pointer == 0
+		matches!(self.condition, Atom::Expression(Expression::Value(ref obj)) if matches!(**obj, Object::Bool(Boolean { value: false })))
+			&& self.scope_pointer == 0
 	}
 }
 
@@ -120,23 +131,91 @@ impl ConditionBlock {
 	}
 }
 
+/// Scopes of a `dene` / `yakala` / `sonunda` group. A pointer of 0 means the block was not written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TryStructure {
+	pub Try: usize,
+	pub Catch: usize,
+	pub ErrorName: Option<String>,
+	pub Finally: usize,
+}
+
+impl TryStructure {
+	pub fn new(Try: usize) -> Self {
+		Self {
+			Try,
+			Catch: 0,
+			ErrorName: None,
+			Finally: 0,
+		}
+	}
+}
+
 #[derive(Debug, Clone)]
 pub struct Scope {
 	pub id: usize,
 	pub parent: Option<usize>,
 	pub children: HashSet<usize>,
 	pub action: Option<ScopeAction>,
-	pub block: Vec<InstructionEnum>,
+	pub block: Vec<(InstructionEnum, Span)>,
 	pub variables: HashMap<String, Object>,
 	pub functions: HashMap<String, Function>,
+	pub modules: HashMap<String, usize>,
 	pub scope_type: ScopeType,
 	pub globals: HashMap<String, Object>,
 }
 
+/// Gets a look at every instruction `ExecuteBlock` runs, right before it runs, and at what happens while it runs.
+/// Used by `zen debug` and `zen run --trace`.
+pub trait ExecutionHook {
+	fn before(&mut self, scope_id: usize, manager: &mut ScopeManager, instr: &InstructionEnum, span: Span) -> miette::Result<()>;
+
+	/// The condition of an `eğer`, `değilse ve` or conditional loop was checked.
+	fn condition(&mut self, _manager: &ScopeManager, _span: Span, _keyword: &str, _result: bool) {}
+
+	/// A loop is about to run its body for the `count`th time, `variable` is the loop variable if it has one.
+	fn iteration(&mut self, _manager: &ScopeManager, _span: Span, _count: usize, _variable: Option<(&str, &Object)>) {}
+
+	/// `name` was assigned, `old` is `None` if it is declared here.
+	fn assignment(&mut self, _manager: &ScopeManager, _span: Span, _name: &str, _old: Option<&Object>, _new: &Object) {}
+}
+
+impl std::fmt::Debug for dyn ExecutionHook {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("ExecutionHook")
+	}
+}
+
+/// Maximum number of nested function calls before an `OzyinelemeHatası` is raised.
+pub const DEFAULT_RECURSION_LIMIT: usize = 1000;
+
+/// Zen function calls recurse through `ExecuteBlock`, so the interpreter runs on a thread with a larger stack than
+/// the main thread's.
+pub const INTERPRETER_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Stack a function call needs to be left with. Below it a `YiginTukendi` is raised whatever the recursion limit is,
+/// instead of overflowing the stack.
+const STACK_RESERVE: usize = 1024 * 1024;
+
+/// Whether the running thread is too close to the end of its stack to call another function.
+pub fn StackExhausted() -> bool {
+	stacker::remaining_stack().is_some_and(|remaining| remaining < STACK_RESERVE)
+}
+
 #[derive(Debug, Clone)]
 pub struct ScopeManager {
 	scopes: HashMap<usize, Scope>,
 	next_id: usize,
+	call_depth: usize,
+	recursion_limit: usize,
+	sources: Vec<(String, String)>,
+	location: Span,
+	strict: bool,
+	loaded_modules: HashMap<PathBuf, usize>,
+	import_stack: Vec<PathBuf>,
+	hook: Option<Rc<RefCell<dyn ExecutionHook>>>,
+	/// Where `yazdır` and `tip` write, standard output when unset.
+	output: Option<Rc<RefCell<String>>>,
 }
 
 impl ScopeManager {
@@ -144,6 +223,15 @@ impl ScopeManager {
 		Self {
 			scopes: HashMap::new(),
 			next_id: 0,
+			call_depth: 0,
+			recursion_limit: DEFAULT_RECURSION_LIMIT,
+			sources: Vec::new(),
+			location: Span::default(),
+			strict: false,
+			loaded_modules: HashMap::new(),
+			import_stack: Vec::new(),
+			hook: None,
+			output: None,
 		}
 	}
 
@@ -159,6 +247,7 @@ impl ScopeManager {
 			block: Vec::new(),
 			variables: HashMap::new(),
 			functions: HashMap::new(),
+			modules: HashMap::new(),
 			globals: HashMap::new(),
 			scope_type: Default,
 		};
@@ -185,6 +274,7 @@ impl ScopeManager {
 			children: HashSet::new(),
 			block: Vec::new(),
 			functions: HashMap::new(),
+			modules: HashMap::new(),
 			variables: HashMap::new(),
 			globals: HashMap::new(),
 			scope_type: Transparent { parent: parent_id },
@@ -210,6 +300,7 @@ impl ScopeManager {
 			children: HashSet::new(),
 			block: Vec::new(),
 			functions: HashMap::new(),
+			modules: HashMap::new(),
 			variables: HashMap::new(),
 			globals: HashMap::new(),
 			scope_type: Isolated,
@@ -223,9 +314,16 @@ impl ScopeManager {
 		id
 	}
 
-	pub fn push_code_to_scope(&mut self, id: usize, instr: &InstructionEnum) {
+	pub fn push_code_to_scope(&mut self, id: usize, instr: &InstructionEnum, span: Span) {
 		if let Some(Scope) = self.get_scope_mut(id) {
-			Scope.block.push(instr.clone());
+			Scope.block.push((instr.clone(), span));
+		}
+	}
+
+	/// Drops the instructions of a scope while keeping its variables, functions and children.
+	pub fn clear_code(&mut self, id: usize) {
+		if let Some(scope) = self.get_scope_mut(id) {
+			scope.block.clear();
 		}
 	}
 
@@ -323,6 +421,9 @@ impl ScopeManager {
 	/// Use this to retrieve variables.
 	pub fn get_var<T: AsRef<str>>(&self, mut scope_id: usize, name: T) -> Option<Object> {
 		let name = name.as_ref();
+		if let Some((namespace, member)) = name.split_once('.') {
+			return self.get_var(self.get_module(scope_id, namespace)?, member);
+		}
 		loop {
 			// Try to find the variable in the current scope
 			if let Some(scope) = self.get_scope(scope_id) {
@@ -349,6 +450,21 @@ impl ScopeManager {
 		None
 	}
 	
+	/// Returns the id of the scope that actually stores the variable, following the same lookup rules as `get_var`.
+	pub fn get_var_owner<T: AsRef<str>>(&self, mut scope_id: usize, name: T) -> Option<usize> {
+		let name = name.as_ref();
+		loop {
+			let scope = self.get_scope(scope_id)?;
+			if scope.variables.contains_key(name) {
+				return Some(scope_id);
+			}
+			if scope.scope_type.is_isolated() {
+				return None;
+			}
+			scope_id = scope.parent?;
+		}
+	}
+
 	pub fn reset_scope_vars(&mut self, scope_id: usize) {
 		if let Some(scope) = self.get_scope_mut(scope_id) {
 			scope.variables.clear();
@@ -369,11 +485,12 @@ impl ScopeManager {
 		depth
 	}
 
-	pub fn declare_function(&mut self, scope_id: usize, name: String, args: Vec<ResolvedParameterData>, scope_pointer: usize) {
+	pub fn declare_function(&mut self, scope_id: usize, name: String, args: Vec<ResolvedParameterData>, scope_pointer: usize, file: usize) {
 		let function_obj = Function {
 			name: name.clone(),
 			args: args.clone(),
 			scope_pointer: scope_pointer,
+			file,
 		};
 		if let Some(scope) = self.scopes.get_mut(&scope_id) {
 			scope.functions.insert(name, function_obj);
@@ -382,14 +499,15 @@ impl ScopeManager {
 
 	pub fn get_function<T: AsRef<str>>(&self, mut scope_id: usize, name: T) -> Option<Function> {
 		let name = name.as_ref();
+		if let Some((namespace, member)) = name.split_once('.') {
+			return self.get_function(self.get_module(scope_id, namespace)?, member);
+		}
 		loop {
 			if let Some(scope) = self.get_scope(scope_id) {
 				if let Some(function) = scope.functions.get(name) {
 					return Some(function.clone());
 				}
-				if scope.scope_type.is_isolated() {
-					break;
-				}
+				// Unlike variables, functions stay visible inside isolated scopes so that they can call themselves.
 				if let Some(parent_id) = scope.parent {
 					scope_id = parent_id;
 				} else {
@@ -402,56 +520,239 @@ impl ScopeManager {
 		None
 	}
 
-	pub fn call_function<T: AsRef<str>>(&mut self, scope_id: usize, name: T, args: Vec<Expression>) -> Option<Object> {
+	/// Makes the root scope of an imported module reachable as `name` from `scope_id` and its children.
+	pub fn declare_module(&mut self, scope_id: usize, name: String, module_scope: usize) {
+		if let Some(scope) = self.scopes.get_mut(&scope_id) {
+			scope.modules.insert(name, module_scope);
+		}
+	}
+
+	pub fn get_module<T: AsRef<str>>(&self, mut scope_id: usize, name: T) -> Option<usize> {
+		let name = name.as_ref();
+		loop {
+			let scope = self.get_scope(scope_id)?;
+			if let Some(module_scope) = scope.modules.get(name) {
+				return Some(*module_scope);
+			}
+			scope_id = scope.parent?;
+		}
+	}
+
+	/// Root scope of a module that has already been executed.
+	pub fn get_loaded_module(&self, path: &Path) -> Option<usize> {
+		self.loaded_modules.get(path).copied()
+	}
+
+	pub fn set_loaded_module(&mut self, path: PathBuf, module_scope: usize) {
+		self.loaded_modules.insert(path, module_scope);
+	}
+
+	/// Files that are being imported right now, outermost first. Used to detect import cycles.
+	pub fn import_stack(&self) -> &[PathBuf] {
+		&self.import_stack
+	}
+
+	pub fn push_import(&mut self, path: PathBuf) {
+		self.import_stack.push(path);
+	}
+
+	pub fn pop_import(&mut self) {
+		self.import_stack.pop();
+	}
+
+	pub fn set_strict(&mut self, strict: bool) {
+		self.strict = strict;
+	}
+
+	pub fn is_strict(&self) -> bool {
+		self.strict
+	}
+
+	pub fn set_recursion_limit(&mut self, limit: usize) {
+		self.recursion_limit = limit;
+	}
+
+	pub fn recursion_limit(&self) -> usize {
+		self.recursion_limit
+	}
+
+	/// Number of function calls the running code is nested in, 0 at the top level of the program.
+	pub fn call_depth(&self) -> usize {
+		self.call_depth
+	}
+
+	pub fn set_hook(&mut self, hook: Option<Rc<RefCell<dyn ExecutionHook>>>) {
+		self.hook = hook;
+	}
+
+	pub fn hook(&self) -> Option<Rc<RefCell<dyn ExecutionHook>>> {
+		self.hook.clone()
+	}
+
+	/// Makes `yazdır` and `tip` write into the returned buffer instead of standard output.
+	pub fn capture_output(&mut self) -> Rc<RefCell<String>> {
+		let buffer = Rc::new(RefCell::new(String::new()));
+		self.output = Some(buffer.clone());
+		buffer
+	}
+
+	/// Prints `values` on one line, separated by spaces.
+	pub fn print<T: Display>(&self, values: impl IntoIterator<Item = T>) {
+		let line = values.into_iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ");
+		match &self.output {
+			Some(buffer) => {
+				let mut buffer = buffer.borrow_mut();
+				buffer.push_str(&line);
+				buffer.push('\n');
+			}
+			None => println!("{line}"),
+		}
+	}
+
+	/// Reports an event to the hook, unless there is none or it is the one running the code.
+	pub fn with_hook(&self, f: impl FnOnce(&mut dyn ExecutionHook, &Self)) {
+		if let Some(hook) = &self.hook
+			&& let Ok(mut hook) = hook.try_borrow_mut()
+		{
+			f(&mut *hook, self);
+		}
+	}
+
+	/// Registers the contents of a file, the returned id is what `Span::file` refers to.
+	pub fn add_source<T: AsRef<str>>(&mut self, name: T, contents: String) -> usize {
+		self.sources.push((name.as_ref().to_owned(), contents));
+		self.sources.len() - 1
+	}
+
+	/// Source of the file the current location is in, used to render runtime diagnostics.
+	pub fn source(&self) -> NamedSource<String> {
+		self.source_of(self.location.file)
+	}
+
+	pub fn source_of(&self, file: usize) -> NamedSource<String> {
+		match self.sources.get(file) {
+			Some((name, contents)) => NamedSource::new(name, contents.clone()).with_language("Zen"),
+			None => NamedSource::new("", String::new()),
+		}
+	}
+
+	/// Name (path) the file was registered with.
+	pub fn source_name(&self, file: usize) -> Option<&str> {
+		self.sources.get(file).map(|(name, _)| name.as_str())
+	}
+
+	/// Records the span of the code that is currently running, errors raised while evaluating point at it.
+	pub fn set_location(&mut self, span: Span) {
+		self.location = span;
+	}
+
+	pub fn span(&self) -> Span {
+		self.location
+	}
+
+	pub fn location(&self) -> SourceSpan {
+		self.location.into()
+	}
+
+	/// Gives a function scope `frame` as its variables and every scope inside it (loop bodies, conditions, ...) an
+	/// empty map, returning the maps they had so that `restore_frames` can put the caller's locals back.
+	fn swap_frames(&mut self, scope_id: usize, frame: HashMap<String, Object>) -> Vec<(usize, HashMap<String, Object>)> {
+		let mut saved = Vec::new();
+		let mut pending = vec![(scope_id, frame)];
+		while let Some((id, frame)) = pending.pop() {
+			if let Some(scope) = self.get_scope_mut(id) {
+				saved.push((id, std::mem::replace(&mut scope.variables, frame)));
+				pending.extend(scope.children.iter().map(|&child| (child, HashMap::new())));
+			}
+		}
+		saved
+	}
+
+	fn restore_frames(&mut self, saved: Vec<(usize, HashMap<String, Object>)>) {
+		for (id, variables) in saved {
+			if let Some(scope) = self.get_scope_mut(id) {
+				scope.variables = variables;
+			}
+		}
+	}
+
+	pub fn call_function<T: AsRef<str>>(
+		&mut self,
+		scope_id: usize,
+		name: T,
+		args: Vec<Expression>,
+		src: NamedSource<String>,
+		span: SourceSpan,
+	) -> miette::Result<Option<Object>> {
 		let name = name.as_ref();
-		let function_scope = self
-			.get_function(scope_id, name)
-			.unwrap_or_else(|| panic!("Function {} not found in scope {}", name, scope_id));
-		let resolved_args = args.iter().map(|x| x.evaluate(scope_id, self)).collect::<Vec<_>>();
+		let Some(function_scope) = self.get_function(scope_id, name) else {
+			return Err(FonksiyonBulunamadı { src, bad_bit: span })?;
+		};
+		let resolved_args = args.iter().map(|x| x.evaluate(scope_id, self)).collect::<miette::Result<Vec<_>>>()?;
 		let funcdef_args = &function_scope.args;
 		if resolved_args.len() > funcdef_args.len() {
-			panic!(
-				"Too many arguments passed to function {}. Expected {}, got {}",
-				name,
-				funcdef_args.len(),
-				resolved_args.len()
-			);
+			return Err(CokFazlaArguman {
+				src,
+				bad_bit: span,
+				expected: Some(funcdef_args.len()),
+				got: Some(resolved_args.len()),
+			})?;
 		}
+		let mut frame = HashMap::new();
 		for (i, param) in funcdef_args.iter().enumerate() {
 			let value = if i < resolved_args.len() {
 				resolved_args[i].clone()
 			} else if let Some(default) = &param.default_value {
 				default.clone()
 			} else {
-				panic!(
-					"Missing argument {} for function {}. Expected {}, got {}",
-					param.name,
-					name,
-					funcdef_args.len(),
-					resolved_args.len()
-				);
+				return Err(EksikArguman {
+					src,
+					bad_bit: span,
+					expected: Some(param.name.clone()),
+				})?;
 			};
 
 			// Type checking
 			if let Some(expected_type) = &param.data_type {
-				value
-					.expectToBe(expected_type.clone(), NamedSource::new("", "".to_owned()), SourceSpan::new(0.into(), 0))
-					.unwrap_or_else(|e| panic!("Type error in function {}: {}", name, e));
+				value.expectToBe(expected_type.clone(), src.clone(), span)?;
 			}
 
-			self.set_var(function_scope.scope_pointer, param.name.clone(), value);
+			frame.insert(param.name.clone(), value);
+		}
+
+		if self.call_depth >= self.recursion_limit {
+			return Err(OzyinelemeHatası {
+				src,
+				bad_bit: span,
+				function: name.to_owned(),
+				limit: self.recursion_limit,
+			})?;
+		}
+		if StackExhausted() {
+			return Err(YiginTukendi {
+				src,
+				bad_bit: span,
+				function: name.to_owned(),
+				depth: self.call_depth,
+			})?;
+		}
+
+		// Every invocation runs on a fresh variable map, the caller's locals are put back once it returns.
+		let caller_frames = self.swap_frames(function_scope.scope_pointer, frame);
+		let caller_location = self.span();
+		self.call_depth += 1;
+		let body_src = self.source_of(function_scope.file);
+		let output = ExecuteBlock(function_scope.scope_pointer, self, body_src, span);
+		self.call_depth -= 1;
+		self.restore_frames(caller_frames);
+		// On failure the location is left pointing at the code that failed.
+		if output.is_ok() {
+			self.set_location(caller_location);
+		}
+
+		match output? {
+			BlockOutput::Return(x) => Ok(Some(x)),
+			_ => Ok(None),
 		}
-		match ExecuteBlock(
-			function_scope.scope_pointer,
-			self,
-			NamedSource::new("", "".to_owned()),
-			SourceSpan::new(0.into(), 0),
-		) {
-			Ok(BlockOutput::Return(x)) => {
-				return Some(x);
-			}
-			_ => {}
-		};
-		None
 	}
 }
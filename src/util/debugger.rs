@@ -0,0 +1,276 @@
+use std::{
+	cell::RefCell,
+	collections::BTreeSet,
+	panic::{AssertUnwindSafe, catch_unwind},
+	path::Path,
+	rc::Rc,
+};
+
+use colored::Colorize;
+use miette::{IntoDiagnostic, NamedSource, SourceSpan};
+use rustyline::{DefaultEditor, error::ReadlineError};
+
+use super::{
+	ScopeManager::{ExecutionHook, Scope, ScopeAction, ScopeManager},
+	process::{BuildScopes, DeclareGlobals, ExecuteBlock, ParseSource},
+	repl::CodeTokens,
+};
+use crate::{
+	features::tokenizer::{CheckTokenVec, InstructionEnum, Span, tokenize_at},
+	parsers::Parsers,
+};
+
+const EXPRESSION_FILENAME: &str = "<ifade>";
+/// Lines shown above and below the paused line.
+const CONTEXT_LINES: usize = 2;
+
+const HELP: &str = "\
+devam, d            Bir kesme noktasına kadar çalıştır
+adım, a             Bir sonraki komutta dur, fonksiyonların içine de gir
+sonraki, s          Bir sonraki komutta dur, çağrılan fonksiyonları tek adımda geç
+çık, ç              İçinde bulunulan fonksiyondan dönülene kadar çalıştır
+dur, b <satır>...   Satırlara kesme noktası koy
+sil <satır>...      Satırlardaki kesme noktalarını kaldır
+noktalar            Kesme noktalarını listele
+değişkenler, v      Durulan kapsamdan görünen değişkenleri yazdır
+yazdır, p <ifade>   İfadeyi durulan kapsamda hesapla
+göster, l           Durulan satırı ve çevresini göster
+bitir, q            Programı sonlandır";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+	/// Runs until a breakpoint.
+	Continue,
+	/// Stops at the next instruction, wherever it is.
+	StepInto,
+	/// Stops at the next instruction that is not inside a call made from the given call depth.
+	StepOver(usize),
+	/// Stops at the first instruction below the given call depth, i.e. once the function returned.
+	StepOut(usize),
+}
+
+struct Debugger {
+	editor: DefaultEditor,
+	/// Breakpoints are lines of this file, modules the program imports are only stepped through.
+	file: usize,
+	breakpoints: BTreeSet<usize>,
+	mode: Mode,
+}
+
+/// Runs `source` under the debugger. Without breakpoints it stops at the first instruction.
+pub fn start(source: String, filename: &str, breakpoints: Vec<usize>, strict: bool, recursion_limit: usize) -> miette::Result<()> {
+	let mut manager = ScopeManager::new();
+	manager.set_recursion_limit(recursion_limit);
+	manager.set_strict(strict);
+	let root_scope = manager.create_scope(None, None);
+
+	if let Ok(path) = Path::new(filename).canonicalize() {
+		manager.push_import(path);
+	}
+	let file = manager.add_source(filename, source.clone());
+	let statements = ParseSource(&source, filename, file, strict)?;
+	BuildScopes(&statements, root_scope, &mut manager);
+	DeclareGlobals(root_scope, &mut manager);
+
+	let debugger = Debugger {
+		editor: DefaultEditor::new().into_diagnostic()?,
+		file,
+		mode: if breakpoints.is_empty() { Mode::StepInto } else { Mode::Continue },
+		breakpoints: breakpoints.into_iter().collect(),
+	};
+	manager.set_hook(Some(Rc::new(RefCell::new(debugger))));
+
+	println!("Zen hata ayıklayıcı. Komutlar için \"yardım\" yazın.");
+	ExecuteBlock(root_scope, &mut manager, NamedSource::new(filename, source.clone()), SourceSpan::new(0.into(), source.len()))?;
+	println!("Program bitti.");
+	Ok(())
+}
+
+impl ExecutionHook for Debugger {
+	fn before(&mut self, scope_id: usize, manager: &mut ScopeManager, instr: &InstructionEnum, span: Span) -> miette::Result<()> {
+		if matches!(instr, InstructionEnum::NoOp) {
+			return Ok(());
+		}
+		let depth = manager.call_depth();
+		let stepped = match self.mode {
+			Mode::Continue => false,
+			Mode::StepInto => true,
+			Mode::StepOver(from) => depth <= from,
+			Mode::StepOut(from) => depth < from,
+		};
+		let breakpoint = span.file == self.file && self.breakpoints.contains(&span.line);
+		if !stepped && !breakpoint {
+			return Ok(());
+		}
+		self.mode = Mode::Continue;
+		self.show(manager, span);
+
+		loop {
+			let line = match self.editor.readline("(zen) ") {
+				Ok(line) => line,
+				Err(ReadlineError::Interrupted) => continue,
+				Err(ReadlineError::Eof) => std::process::exit(0),
+				Err(err) => return Err(err).into_diagnostic(),
+			};
+			let line = line.trim();
+			if line.is_empty() {
+				continue;
+			}
+			let _ = self.editor.add_history_entry(line);
+			let (command, argument) = line.split_once(char::is_whitespace).map_or((line, ""), |(a, b)| (a, b.trim()));
+			match command {
+				"devam" | "d" => break,
+				"adım" | "a" => {
+					self.mode = Mode::StepInto;
+					break;
+				}
+				"sonraki" | "s" => {
+					self.mode = Mode::StepOver(depth);
+					break;
+				}
+				"çık" | "ç" => {
+					self.mode = Mode::StepOut(depth);
+					break;
+				}
+				"dur" | "b" => self.set_breakpoints(argument, manager, true),
+				"sil" => self.set_breakpoints(argument, manager, false),
+				"noktalar" => match self.breakpoints.is_empty() {
+					true => println!("Kesme noktası yok."),
+					false => println!("{}", self.breakpoints.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")),
+				},
+				"değişkenler" | "v" => Variables(scope_id, manager),
+				"yazdır" | "p" => Evaluate(argument, scope_id, manager),
+				"göster" | "l" => self.show(manager, span),
+				"yardım" | "h" => println!("{HELP}"),
+				"bitir" | "q" => std::process::exit(0),
+				_ => eprintln!("Bilinmeyen komut: {command}. Komutlar için \"yardım\" yazın."),
+			}
+		}
+		Ok(())
+	}
+}
+
+impl Debugger {
+	/// Prints where the program stopped, with a few lines around it. Breakpoints are marked with `*`.
+	fn show(&self, manager: &ScopeManager, span: Span) {
+		let source = manager.source_of(span.file);
+		let name = manager.source_name(span.file).unwrap_or_default();
+		println!("{}", format!("{name}:{}", span.line).bold());
+		let lines = source.inner().lines().collect::<Vec<_>>();
+		let first = span.line.saturating_sub(CONTEXT_LINES).max(1);
+		let last = (span.line + CONTEXT_LINES).min(lines.len());
+		for number in first..=last {
+			let mark = if span.file == self.file && self.breakpoints.contains(&number) { "*" } else { " " };
+			let row = format!("{mark}{number:>4} | {}", lines[number - 1].replace('\t', "    "));
+			if number == span.line {
+				println!("{}", format!("→{row}").yellow());
+			} else {
+				println!(" {row}");
+			}
+		}
+	}
+
+	fn set_breakpoints(&mut self, argument: &str, manager: &ScopeManager, add: bool) {
+		let count = manager.source_of(self.file).inner().lines().count();
+		if argument.is_empty() {
+			eprintln!("Satır numarası gerekli.");
+		}
+		for word in argument.split_whitespace() {
+			match word.parse::<usize>() {
+				Ok(line) if (1..=count).contains(&line) => {
+					if add {
+						self.breakpoints.insert(line);
+						println!("{line}. satıra kesme noktası kondu.");
+					} else if self.breakpoints.remove(&line) {
+						println!("{line}. satırdaki kesme noktası kaldırıldı.");
+					} else {
+						eprintln!("{line}. satırda kesme noktası yok.");
+					}
+				}
+				_ => eprintln!("Geçersiz satır: {word}. Dosyada {count} satır var."),
+			}
+		}
+	}
+}
+
+/// Prints the variables `scope_id` can see, scope by scope from the innermost one out, the same way `get_var`
+/// looks them up.
+fn Variables(scope_id: usize, manager: &ScopeManager) {
+	let mut current = Some(scope_id);
+	let mut empty = true;
+	while let Some(id) = current
+		&& let Some(scope) = manager.get_scope(id)
+	{
+		if !scope.variables.is_empty() {
+			println!("{}", format!("Kapsam {id} ({})", DescribeScope(scope)).bold());
+			let mut variables = scope.variables.iter().collect::<Vec<_>>();
+			variables.sort_by_key(|(name, _)| *name);
+			for (name, value) in variables {
+				println!("\t{name} = {value} ({})", value.get_type());
+			}
+			empty = false;
+		}
+		if scope.scope_type.is_isolated() {
+			break;
+		}
+		current = scope.parent;
+	}
+	if let Some(root) = manager.get_scope(0)
+		&& !root.globals.is_empty()
+	{
+		println!("{}", "Küresel".bold());
+		let mut globals = root.globals.iter().collect::<Vec<_>>();
+		globals.sort_by_key(|(name, _)| *name);
+		for (name, value) in globals {
+			println!("\t{name} = {value} ({})", value.get_type());
+		}
+	}
+	if empty {
+		println!("Bu kapsamda henüz değişken yok.");
+	}
+}
+
+fn DescribeScope(scope: &Scope) -> String {
+	match &scope.action {
+		None | Some(ScopeAction::RootScope) => "program".to_owned(),
+		Some(ScopeAction::Function { name, .. }) => format!("fonksiyon {name}"),
+		Some(ScopeAction::Repeat(_)) => "kez tekrarla".to_owned(),
+		Some(ScopeAction::For(.., name)) => format!("{name} arasında dolan"),
+		Some(ScopeAction::ForIn { name, .. }) => format!("{name} içinde dolan"),
+		Some(ScopeAction::WhileTrue) => "sürekli tekrarla".to_owned(),
+		Some(ScopeAction::RepeatUntil(_)) => "olana kadar tekrarla".to_owned(),
+		Some(ScopeAction::RepeatWhile(_)) => "olmadığı sürece tekrarla".to_owned(),
+		Some(ScopeAction::Condition(_)) => "eğer".to_owned(),
+		Some(ScopeAction::Try) => "dene".to_owned(),
+		Some(ScopeAction::Catch(_)) => "yakala".to_owned(),
+		Some(ScopeAction::Finally) => "sonunda".to_owned(),
+		Some(ScopeAction::Test(name)) => format!("test \"{name}\""),
+	}
+}
+
+/// Evaluates `text` in the paused scope. Functions it calls run to completion, the debugger does not stop in them.
+fn Evaluate(text: &str, scope_id: usize, manager: &mut ScopeManager) {
+	if text.is_empty() {
+		eprintln!("Hesaplanacak ifade gerekli.");
+		return;
+	}
+	let file = manager.add_source(EXPRESSION_FILENAME, text.to_owned());
+	let line_feed = CodeTokens(&tokenize_at(text, file, 0, 1));
+	let atom = match line_feed.is_all_ok().then(|| Parsers::parse_value(line_feed)) {
+		Some(Ok(atom)) => atom,
+		_ => {
+			eprintln!("Geçerli bir ifade değil: {text}");
+			return;
+		}
+	};
+
+	let location = manager.span();
+	manager.set_location(Span::new(file, 0..text.len(), 1));
+	let outcome = catch_unwind(AssertUnwindSafe(|| atom.resolve(scope_id, manager)?.evaluate(scope_id, manager)));
+	manager.set_location(location);
+	match outcome {
+		Ok(Ok(value)) => println!("{value} ({})", value.get_type()),
+		Ok(Err(report)) => eprintln!("{:?}", report),
+		Err(_) => eprintln!("İfade hesaplanırken bir hata oluştu."),
+	}
+}
@@ -1,2 +1,4 @@
 pub mod ScopeManager;
+pub mod debugger;
 pub mod process;
+pub mod repl;
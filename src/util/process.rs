@@ -1,21 +1,32 @@
-use super::ScopeManager::{ConditionBlock, ConditionStructure, Scope};
-use crate::features::tokenizer::{AssignmentMethod, CheckTokenVec, ConditionBlockType, Atom, RemoveQuotes};
-use crate::library::Error::{CokFazlaArguman, EksikArguman, FonksiyonBulunamadı, GirintiHatası, TokenHatası};
-use crate::library::Types::{Array, Number, Object, TimeUnit};
+use super::ScopeManager::{ConditionBlock, ConditionStructure, Scope, TryStructure};
+use crate::features::tokenizer::{AssignmentMethod, ConditionBlockType, Atom, RemoveQuotes};
+use crate::library::Error::{
+	AnahtarBulunamadı, BoslukGirintisi, CokFazlaArguman, DegiskenBulunamadı, DeneOlmadanYakala, DogrulamaBasarisiz, DonguDisinda, DonguselIceAktarma, EgerOlmadanDegilse, EksikArguman,
+	ErisilemeyenKod, EsitDegil, FirlatilanHata, FonksiyonBulunamadı, FonksiyonDisindaDondur, GirintiHatası, IndeksHatası, KapanmamisParantez, KullanilmayanDegisken, ModulBulunamadı,
+	OzyinelemeHatası, TipHatası, TokenHatası, YiginTukendi,
+};
+use crate::library::Types::{Array, Map, Number, Object, TimeUnit, ZenError};
 use crate::parsers::Parsers::Expression;
 use crate::{
-	DebugVec, Print, PrintVec,
-	features::tokenizer::{InstructionEnum, TokenData, TokenTable, tokenize},
+	DebugVec, Print,
+	features::{
+		ast, preprocessor,
+		tokenizer::{InstructionEnum, LayoutError, Span, Statement, TokenData, TokenTable, tokenize_file},
+		trace::{TraceFormat, Tracer},
+	},
 	library::Types::CutFromStart,
-	parsers::Parsers::{self, ParserOutput},
+	parsers::Parsers,
 	util::ScopeManager::{ScopeAction, ScopeManager},
 };
+use chumsky::error::SimpleReason;
 use chumsky::prelude::*;
 use colored::Colorize;
 use defer::defer;
-use miette::GraphicalReportHandler;
-use miette::{Error, NamedSource, SourceSpan};
+use miette::{NamedSource, SourceSpan, miette};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::path::Path;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum BlockOutput {
@@ -31,76 +42,133 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 	let block = scope.block.clone();
 	let mut result = BlockOutput::None;
 
-	for line in block.clone() {
+	'block: for (line, line_span) in block.clone() {
+		manager.set_location(line_span);
+		// The hook is already borrowed when the code runs on its behalf, e.g. a call in an expression the debugger evaluates.
+		if let Some(hook) = manager.hook()
+			&& let Ok(mut hook) = hook.try_borrow_mut()
+		{
+			hook.before(scope_id, manager, &line, line_span)?;
+		}
+		let span: SourceSpan = line_span.into();
 		match line.clone() {
 			InstructionEnum::Print(expr) => {
-				PrintVec!(
-					expr.iter()
-						.map(|x| x.resolve(scope_id, manager).evaluate(scope_id, manager))
-						.collect::<Vec<_>>()
-				);
+				let values = expr
+					.iter()
+					.map(|x| x.resolve(scope_id, manager)?.evaluate(scope_id, manager))
+					.collect::<miette::Result<Vec<_>>>()?;
+				manager.print(values);
 			}
 			InstructionEnum::Type(expr) => {
-				PrintVec!(
-					expr.iter()
-						.map(|x| x.resolve(scope_id, manager).evaluate(scope_id, manager))
-						.map(|x| format!("Değer: {}, Tip: {}", x, x.get_type()))
-						.collect::<Vec<_>>()
-				);
+				let values = expr
+					.iter()
+					.map(|x| x.resolve(scope_id, manager)?.evaluate(scope_id, manager))
+					.collect::<miette::Result<Vec<_>>>()?;
+				manager.print(values.iter().map(|x| format!("Değer: {}, Tip: {}", x, x.get_type())));
 			}
 			InstructionEnum::VariableDeclaration(name, value, method) => {
-				let evaluated_value = value.resolve(scope_id, manager).evaluate(scope_id, manager);
-				let new_value = match method {
-					AssignmentMethod::Set => evaluated_value,
-					AssignmentMethod::Add => manager.get_var(scope_id, name.clone()).expect("No previous value") + evaluated_value,
-					AssignmentMethod::Sub => manager.get_var(scope_id, name.clone()).expect("No previous value") - evaluated_value,
-					AssignmentMethod::Mul => manager.get_var(scope_id, name.clone()).expect("No previous value") * evaluated_value,
-					AssignmentMethod::Div => manager.get_var(scope_id, name.clone()).expect("No previous value") / evaluated_value,
+				let evaluated_value = value.resolve(scope_id, manager)?.evaluate(scope_id, manager)?;
+				let old_value = manager.get_var(scope_id, name.clone());
+				let new_value = method.apply(old_value.clone(), evaluated_value, src.clone(), span)?;
+				manager.with_hook(|hook, manager| hook.assignment(manager, line_span, &name, old_value.as_ref(), &new_value));
+				// A variable that already exists is updated where it lives, a new one is declared here. set_var
+				// redirects transparent scopes to the first non-transparent parent itself.
+				let owner = manager.get_var_owner(scope_id, &name).unwrap_or(scope_id);
+				manager.set_var(owner, name, new_value);
+			}
+			InstructionEnum::IndexAssignment { name, path, value, method } => {
+				let path = path.iter().map(|x| x.evaluate(scope_id, manager)).collect::<miette::Result<Vec<_>>>()?;
+				let evaluated_value = value.resolve(scope_id, manager)?.evaluate(scope_id, manager)?;
+				let Some(owner) = manager.get_var_owner(scope_id, &name) else {
+					return Err(DegiskenBulunamadı { src, bad_bit: span })?;
 				};
-
-				let mut temp_scope_id = scope_id.clone();
-				if manager.get_scope(scope_id).unwrap().scope_type.is_transparent() {
-					// If the scope is transparent, we need to find the first non-transparent parent scope
-					while let Some(parent) = manager.get_parent(temp_scope_id) {
-						if !manager.get_scope(parent).unwrap().scope_type.is_transparent() {
-							temp_scope_id = parent;
-							break;
+				let old_value = manager.get_var(owner, &name).unwrap();
+				let mut container = old_value.clone();
+				container.assign_at(&path, &method, evaluated_value, src.clone(), span)?;
+				manager.with_hook(|hook, manager| hook.assignment(manager, line_span, &name, Some(&old_value), &container));
+				manager.set_var(owner, name, container);
+			}
+			InstructionEnum::WhileTrue { scope_pointer } => {
+				let mut count = 0;
+				loop {
+					count += 1;
+					manager.with_hook(|hook, manager| hook.iteration(manager, line_span, count, None));
+					match ExecuteBlock(scope_pointer, manager, src.clone(), span) {
+						Ok(BlockOutput::Break) => break,
+						Ok(BlockOutput::Continue) => continue,
+						Ok(BlockOutput::Return(x)) => {
+							result = BlockOutput::Return(x);
+							break 'block;
+						}
+						Ok(BlockOutput::None) => {}
+						Err(e) => {
+							return Err(e);
 						}
 					}
-				} else {
-					temp_scope_id = scope_id;
 				}
-
-				manager.set_var(temp_scope_id, name, new_value);
 			}
-			InstructionEnum::WhileTrue { scope_pointer } => loop {
-				match ExecuteBlock(scope_pointer, manager, src.clone(), span) {
-					Ok(BlockOutput::Break) => break,
-					Ok(BlockOutput::Continue) => continue,
-					Ok(BlockOutput::Return(x)) => {
-						result = BlockOutput::Return(x);
+			InstructionEnum::RepeatUntil { condition, scope_pointer } => {
+				let mut count = 0;
+				loop {
+					count += 1;
+					manager.with_hook(|hook, manager| hook.iteration(manager, line_span, count, None));
+					match ExecuteBlock(scope_pointer, manager, src.clone(), span) {
+						Ok(BlockOutput::Break) => break,
+						Ok(BlockOutput::Return(x)) => {
+							result = BlockOutput::Return(x);
+							break 'block;
+						}
+						Ok(BlockOutput::Continue) | Ok(BlockOutput::None) => {}
+						Err(e) => {
+							return Err(e);
+						}
+					}
+					let passed = condition.resolve(scope_id, manager)?.isTruthy(scope_id, manager)?;
+					manager.with_hook(|hook, manager| hook.condition(manager, line_span, "olana kadar tekrarla", passed));
+					if passed {
 						break;
 					}
-					Ok(BlockOutput::None) => {}
-					Err(e) => {
-						return Err(e);
+				}
+			}
+			InstructionEnum::RepeatWhile { condition, scope_pointer } => {
+				let mut count = 0;
+				loop {
+					let passed = condition.resolve(scope_id, manager)?.isTruthy(scope_id, manager)?;
+					manager.with_hook(|hook, manager| hook.condition(manager, line_span, "olmadığı sürece tekrarla", passed));
+					if passed {
+						break;
+					}
+					count += 1;
+					manager.with_hook(|hook, manager| hook.iteration(manager, line_span, count, None));
+					match ExecuteBlock(scope_pointer, manager, src.clone(), span) {
+						Ok(BlockOutput::Break) => break,
+						Ok(BlockOutput::Continue) => continue,
+						Ok(BlockOutput::Return(x)) => {
+							result = BlockOutput::Return(x);
+							break 'block;
+						}
+						Ok(BlockOutput::None) => {}
+						Err(e) => {
+							return Err(e);
+						}
 					}
 				}
-			},
+			}
 			InstructionEnum::Repeat { repeat_count, scope_pointer } => {
-				for _ in 0..(repeat_count
-					.resolve(scope_id, manager)
-					.evaluate(scope_id, manager)
-					.expectToBeNumber(src.clone(), span)?
+				for count in 0..(repeat_count
+					.resolve(scope_id, manager)?
+					.evaluate(scope_id, manager)?
+					.expectToBeNumber(src.clone(), repeat_count.span().map_or(span, Into::into))?
 					.value)
 					.floor() as i64
 				{
+					manager.with_hook(|hook, manager| hook.iteration(manager, line_span, count as usize + 1, None));
 					match ExecuteBlock(scope_pointer, manager, src.clone(), span) {
 						Ok(BlockOutput::Break) => break,
 						Ok(BlockOutput::Continue) => continue,
 						Ok(BlockOutput::Return(x)) => {
 							result = BlockOutput::Return(x);
-							break;
+							break 'block;
 						}
 						Ok(BlockOutput::None) => {}
 						Err(e) => {
@@ -116,34 +184,49 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 				name,
 				scope_pointer,
 			} => {
-				for index in ((from
-					.resolve(scope_id, manager)
-					.evaluate(scope_id, manager)
-					.expectToBeNumber(src.clone(), span)?
-					.value)
-					.floor() as i64
-					..(to
-						.resolve(scope_id, manager)
-						.evaluate(scope_id, manager)
-						.expectToBeNumber(src.clone(), span)?
-						.value)
-						.floor() as i64)
-					.step_by(
-						(step
-							.unwrap_or(Expression::from(Object::from(1f64)).into())
-							.resolve(scope_id, manager)
-							.evaluate(scope_id, manager)
-							.expectToBeNumber(src.clone(), span)?
-							.value)
-							.floor() as usize,
-					) {
-					manager.set_var(scope_pointer, name.clone(), Object::from(index as f64));
+				let from = from
+					.resolve(scope_id, manager)?
+					.evaluate(scope_id, manager)?
+					.expectToBeNumber(src.clone(), from.span().map_or(span, Into::into))?
+					.value
+					.floor() as i64;
+				let to = to
+					.resolve(scope_id, manager)?
+					.evaluate(scope_id, manager)?
+					.expectToBeNumber(src.clone(), to.span().map_or(span, Into::into))?
+					.value
+					.floor() as i64;
+				let step = step
+					.clone()
+					.unwrap_or(Expression::from(Object::from(1f64)).into())
+					.resolve(scope_id, manager)?
+					.evaluate(scope_id, manager)?
+					.expectToBeNumber(src.clone(), step.as_ref().and_then(|x| x.span()).map_or(span, Into::into))?
+					.value
+					.floor();
+				// A step of zero would never reach the end, a negative one can't be a usize.
+				if step < 1.0 {
+					return Err(TipHatası::expected(
+						"Adımın sıfırdan büyük bir sayı olması bekleniyordu.".to_owned(),
+						step.to_string(),
+						src,
+						span,
+					)
+					.into());
+				}
+				for (count, index) in (from..to)
+					.step_by(step as usize)
+					.enumerate()
+				{
+					let value = Object::from(index as f64);
+					manager.with_hook(|hook, manager| hook.iteration(manager, line_span, count + 1, Some((&name, &value))));
+					manager.set_var(scope_pointer, name.clone(), value);
 					match ExecuteBlock(scope_pointer, manager, src.clone(), span) {
 						Ok(BlockOutput::Break) => break,
 						Ok(BlockOutput::Continue) => continue,
 						Ok(BlockOutput::Return(x)) => {
 							result = BlockOutput::Return(x);
-							break;
+							break 'block;
 						}
 						Ok(BlockOutput::None) => {}
 						Err(e) => {
@@ -153,15 +236,33 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 				}
 			}
 			InstructionEnum::ForIn { name, step, varname, scope_pointer } => {
-				let iterable: Array = manager.get_var(scope_pointer, name.clone()).expect(format!("No variable named {name} was found").as_str()).into();
+				let iterable: Array = match manager.get_var(scope_pointer, name.clone()) {
+					// Maps are iterated over their keys.
+					Some(Object::Map(map)) => map.keys().into(),
+					Some(other) => other.into(),
+					None => return Err(DegiskenBulunamadı { src, bad_bit: span })?,
+				};
 				let step = match step {
-					Some(thing) => thing.resolve(scope_id, manager).evaluate(scope_id, manager).forceIntoNumber(),
+					Some(thing) => thing.resolve(scope_id, manager)?.evaluate(scope_id, manager)?.forceIntoNumber(),
 					None => Number::from(1.0),
 				};
+				// Same as `arasında`: a step of zero never moves on, a negative one would become zero.
+				if step.value.floor() < 1.0 {
+					return Err(TipHatası::expected(
+						"Adımın sıfırdan büyük bir sayı olması bekleniyordu.".to_owned(),
+						step.to_string(),
+						src,
+						span,
+					)
+					.into());
+				}
 
 				let mut i = 0;
+				let mut count = 0;
 				while i < iterable.value.len() {
 					let x = &iterable[i];
+					count += 1;
+					manager.with_hook(|hook, manager| hook.iteration(manager, line_span, count, Some((&varname, x))));
 					manager.set_var(scope_pointer, varname.clone(), x.clone());
 					match ExecuteBlock(scope_pointer, manager, src.clone(), span) {
 						Ok(BlockOutput::Break) => break,
@@ -171,7 +272,7 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 						}
 						Ok(BlockOutput::Return(x)) => {
 							result = BlockOutput::Return(x);
-							break;
+							break 'block;
 						}
 						Ok(BlockOutput::None) => {}
 						Err(e) => {
@@ -182,10 +283,79 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 				}
 			}
 			InstructionEnum::Function { name, args, scope_pointer } => {
-				let resolved_args = args.iter().map(|x| x.toResolved(scope_id, manager)).collect::<Vec<_>>();
-				manager.declare_function(scope_id, name.clone(), resolved_args, scope_pointer.clone());
+				let resolved_args = args.iter().map(|x| x.toResolved(scope_id, manager)).collect::<miette::Result<Vec<_>>>()?;
+				manager.declare_function(scope_id, name.clone(), resolved_args, scope_pointer, line_span.file);
+			}
+			InstructionEnum::Import { path, namespace } => {
+				ImportModule(&path, &namespace, scope_id, manager, src.clone(), span)?;
+			}
+			InstructionEnum::CallFunction { name, args } => {
+				let mut args_evaluated = Vec::new();
+				for arg in args {
+					args_evaluated.push(Expression::from(arg.resolve(scope_id, manager)?.evaluate(scope_id, manager)?));
+				}
+				// Statement level calls are only run for their side effects, the return value is dropped.
+				manager.call_function(scope_id, name, args_evaluated, src.clone(), span)?;
+			}
+			InstructionEnum::TryCatch(structure) => {
+				let mut outcome = ExecuteBlock(structure.Try, manager, src.clone(), span);
+				if structure.Catch != 0
+					&& let Err(report) = outcome
+				{
+					if let Some(name) = &structure.ErrorName {
+						let error = ErrorObject(&report, manager.span().line);
+						manager.set_var(structure.Catch, name.clone(), error);
+					}
+					outcome = ExecuteBlock(structure.Catch, manager, src.clone(), span);
+				}
+				if structure.Finally != 0 {
+					// `durdur`, `devam et` or `döndür` inside `sonunda` wins over whatever the other blocks did.
+					match ExecuteBlock(structure.Finally, manager, src.clone(), span)? {
+						BlockOutput::None => {}
+						other => outcome = Ok(other),
+					}
+				}
+				match outcome? {
+					BlockOutput::None => {}
+					other => {
+						result = other;
+						break 'block;
+					}
+				}
+			}
+			InstructionEnum::Throw(value) => {
+				let message = value.resolve(scope_id, manager)?.evaluate(scope_id, manager)?;
+				return Err(FirlatilanHata {
+					src,
+					bad_bit: span,
+					message: message.to_string(),
+				}
+				.into());
+			}
+			// The body of a test only runs under `zen test`, see `test::run_tests`.
+			InstructionEnum::Test { .. } => {}
+			InstructionEnum::Assert(value) => {
+				if !value.resolve(scope_id, manager)?.isTruthy(scope_id, manager)? {
+					return Err(DogrulamaBasarisiz {
+						src,
+						bad_bit: value.span().map_or(span, Into::into),
+					}
+					.into());
+				}
+			}
+			InstructionEnum::AssertEqual(left, right) => {
+				let left = left.resolve(scope_id, manager)?.evaluate(scope_id, manager)?;
+				let right = right.resolve(scope_id, manager)?.evaluate(scope_id, manager)?;
+				if left != right {
+					return Err(EsitDegil {
+						src,
+						bad_bit: span,
+						left: ast::object(&left),
+						right: ast::object(&right),
+					}
+					.into());
+				}
 			}
-			InstructionEnum::CallFunction { name, args } => {}
 			InstructionEnum::Break => {
 				result = BlockOutput::Break;
 				break;
@@ -195,13 +365,15 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 				break;
 			}
 			InstructionEnum::Return(expr) => {
-				let return_value = expr.resolve(scope_id, manager).evaluate(scope_id, manager);
+				let return_value = expr.resolve(scope_id, manager)?.evaluate(scope_id, manager)?;
 				result = BlockOutput::Return(return_value);
 				break;
 			}
 			InstructionEnum::Condition(condition) => {
 				// Evaluate the main condition
-				if condition.If.condition.isTruthy(scope_id, manager) {
+				let passed = condition.If.condition.resolve(scope_id, manager)?.isTruthy(scope_id, manager)?;
+				manager.with_hook(|hook, manager| hook.condition(manager, line_span, "eğer", passed));
+				if passed {
 					match ExecuteBlock(condition.If.scope_pointer, manager, src.clone(), span) {
 						Ok(BlockOutput::Break) => {
 							result = BlockOutput::Break;
@@ -213,7 +385,7 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 						}
 						Ok(BlockOutput::Return(x)) => {
 							result = BlockOutput::Return(x);
-							break;
+							break 'block;
 						}
 						Ok(BlockOutput::None) => {}
 						Err(e) => {
@@ -224,19 +396,22 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 					// Check elifs
 					let mut executed = false;
 					for elif in &condition.Elif {
-						if elif.condition.isTruthy(scope_id, manager) {
+						let passed = elif.condition.resolve(scope_id, manager)?.isTruthy(scope_id, manager)?;
+						let elif_span = elif.condition.span().unwrap_or(line_span);
+						manager.with_hook(|hook, manager| hook.condition(manager, elif_span, "değilse ve", passed));
+						if passed {
 							match ExecuteBlock(elif.scope_pointer, manager, src.clone(), span) {
 								Ok(BlockOutput::Break) => {
 									result = BlockOutput::Break;
-									break;
+									break 'block;
 								}
 								Ok(BlockOutput::Continue) => {
 									result = BlockOutput::Continue;
-									break;
+									break 'block;
 								}
 								Ok(BlockOutput::Return(x)) => {
 									result = BlockOutput::Return(x);
-									break;
+									break 'block;
 								}
 								Ok(BlockOutput::None) => {}
 								Err(e) => {
@@ -272,21 +447,11 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 			}
 			InstructionEnum::Wait { amount, unit } => {
 				let wait_time = amount
-					.resolve(scope_id, manager)
-					.evaluate(scope_id, manager)
-					.expectToBeNumber(src.clone(), span)?
+					.resolve(scope_id, manager)?
+					.evaluate(scope_id, manager)?
+					.expectToBeNumber(src.clone(), amount.span().map_or(span, Into::into))?
 					.value;
-				let wait_unit = match unit {
-					TimeUnit::Millisecond => std::time::Duration::from_millis(wait_time as u64),
-					TimeUnit::Second => std::time::Duration::from_secs(wait_time as u64),
-					TimeUnit::Minute => std::time::Duration::from_secs(wait_time as u64 * 60),
-					TimeUnit::Hour => std::time::Duration::from_secs(wait_time as u64 * 3600),
-					TimeUnit::Day => std::time::Duration::from_secs(wait_time as u64 * 86400),
-					TimeUnit::Week => std::time::Duration::from_secs(wait_time as u64 * 604800),
-					TimeUnit::Month => std::time::Duration::from_secs(wait_time as u64 * 2592000), // Approximation
-					TimeUnit::Year => std::time::Duration::from_secs(wait_time as u64 * 31536000), // Approximation
-				};
-				std::thread::sleep(wait_unit);
+				std::thread::sleep(unit.duration(wait_time));
 			}
 			_ => todo!(),
 		}
@@ -294,186 +459,358 @@ pub fn ExecuteBlock(scope_id: usize, manager: &mut ScopeManager, src: NamedSourc
 	Ok(result)
 }
 
-pub fn ProcessLine(
-	full_source: String,
-	raw_line_feed_string: String,
-	line_feed: Vec<TokenData>,
-	instr: (ParserOutput, InstructionEnum),
-	current_scope_id: &mut usize,
-	manager: &mut ScopeManager,
-	opts: &Runopts,
-	fileandline: (&str, usize), // conditional_grup:
-) -> miette::Result<()> {
-	let line_indent = line_feed.iter().take_while(|x| x.token == TokenTable::Tab).count();
-	let mut scope_depth = manager.get_depth(*current_scope_id);
-	if line_indent < scope_depth {
-		while scope_depth > line_indent {
-			if let Some(parent) = manager.get_parent(*current_scope_id) {
-				*current_scope_id = parent;
-				scope_depth -= 1;
-			} else {
-				break;
-			}
-		}
-	} else if line_indent > scope_depth + 1 {
-		if opts.strict {
-			return Err(GirintiHatası {
-				src: NamedSource::new(fileandline.0, full_source).with_language("Zen"),
-				bad_bit: SourceSpan::new(scope_depth.into(), (line_indent - scope_depth) as usize),
-			})?;
-		}
-	}
-
-	if instr.0.indent {
-		let mut instr_enum = instr.clone().1;
+/// Turns parsed statements into the scope tree under `scope_id`: every block header gets a scope holding its body,
+/// `eğer` / `değilse` chains are grouped into one `Condition` and `dene` / `yakala` / `sonunda` into one `TryCatch`.
+pub fn BuildScopes(statements: &[Statement], scope_id: usize, manager: &mut ScopeManager) {
+	for statement in statements {
+		let mut instr_enum = statement.instr.clone();
 		let new_scope = match instr_enum {
 			InstructionEnum::IfBlock { .. }
 			| InstructionEnum::ElifBlock { .. }
 			| InstructionEnum::ElseBlock { .. }
 			| InstructionEnum::For { .. }
 			| InstructionEnum::WhileTrue { .. }
-			| InstructionEnum::Repeat { .. } => manager.create_transparent_scope(*current_scope_id, Some(instr_enum.as_block_action())),
-			InstructionEnum::Function { .. } => manager.create_isolated_scope(*current_scope_id, Some(instr_enum.as_block_action())),
-			_ => manager.create_scope(Some(*current_scope_id), Some(instr_enum.as_block_action())),
+			| InstructionEnum::RepeatUntil { .. }
+			| InstructionEnum::RepeatWhile { .. }
+			| InstructionEnum::Repeat { .. }
+			| InstructionEnum::TryBlock { .. }
+			| InstructionEnum::CatchBlock { .. }
+			| InstructionEnum::FinallyBlock { .. } => manager.create_transparent_scope(scope_id, Some(instr_enum.as_block_action())),
+			InstructionEnum::Function { .. } => manager.create_isolated_scope(scope_id, Some(instr_enum.as_block_action())),
+			InstructionEnum::ForIn { .. } | InstructionEnum::Test { .. } => manager.create_scope(Some(scope_id), Some(instr_enum.as_block_action())),
+			_ => {
+				manager.push_code_to_scope(scope_id, &instr_enum, statement.span);
+				continue;
+			}
 		};
 
 		match instr_enum {
 			InstructionEnum::IfBlock { .. } => {
 				instr_enum = InstructionEnum::Condition(ConditionBlock::new(ConditionStructure {
 					scope_pointer: new_scope,
-					condition: instr_enum.as_expression().resolve(*current_scope_id, manager),
+					condition: instr_enum.as_expression(),
 				}));
-				manager.push_code_to_scope(*current_scope_id, &instr_enum);
-				*current_scope_id = new_scope;
+				manager.push_code_to_scope(scope_id, &instr_enum, statement.span);
 			}
-			InstructionEnum::ElifBlock { .. } => {
-				let resolved_condition = instr_enum.as_expression().resolve(*current_scope_id, manager);
-				if let Some(last_instr) = manager.get_scope_mut(*current_scope_id).unwrap().block.last_mut() {
-					if let InstructionEnum::Condition(con) = last_instr {
-						con.push_elif(ConditionStructure {
-							scope_pointer: new_scope,
-							condition: resolved_condition,
-						});
+			InstructionEnum::ElifBlock { .. } | InstructionEnum::ElseBlock { .. } => {
+				let branch = ConditionStructure {
+					scope_pointer: new_scope,
+					condition: instr_enum.as_expression(),
+				};
+				if let Some((InstructionEnum::Condition(con), _)) = manager.get_scope_mut(scope_id).unwrap().block.last_mut() {
+					match instr_enum {
+						InstructionEnum::ElifBlock { .. } => con.push_elif(branch),
+						_ => con.push_else(branch),
 					}
 				}
-				*current_scope_id = new_scope;
 			}
-			InstructionEnum::ElseBlock { .. } => {
-				let resolved_condition = instr_enum.as_expression().resolve(*current_scope_id, manager);
-				if let Some(last_instr) = manager.get_scope_mut(*current_scope_id).unwrap().block.last_mut() {
-					if let InstructionEnum::Condition(con) = last_instr {
-						con.push_else(ConditionStructure {
-							scope_pointer: new_scope,
-							condition: resolved_condition,
-						});
+			InstructionEnum::TryBlock { .. } => {
+				instr_enum = InstructionEnum::TryCatch(TryStructure::new(new_scope));
+				manager.push_code_to_scope(scope_id, &instr_enum, statement.span);
+			}
+			InstructionEnum::CatchBlock { .. } | InstructionEnum::FinallyBlock { .. } => {
+				if let Some((InstructionEnum::TryCatch(structure), _)) = manager.get_scope_mut(scope_id).unwrap().block.last_mut() {
+					match &instr_enum {
+						InstructionEnum::CatchBlock { name, .. } => {
+							structure.Catch = new_scope;
+							structure.ErrorName = name.clone();
+						}
+						_ => structure.Finally = new_scope,
 					}
 				}
-				*current_scope_id = new_scope;
 			}
 			_ => {
 				instr_enum.set_block_pointer(new_scope);
-				manager.push_code_to_scope(*current_scope_id, &instr_enum);
-				*current_scope_id = new_scope;
+				manager.push_code_to_scope(scope_id, &instr_enum, statement.span);
 			}
 		}
+		BuildScopes(&statement.body, new_scope, manager);
+	}
+}
+
+#[derive(Default)]
+pub struct Runopts {
+	pub verbose: bool,
+	pub strict: bool,
+	/// Print the parsed tree before running.
+	pub print_ast: bool,
+	/// Print the source as the parser sees it (see `preprocessor::layout`).
+	pub print_preprocess_output: bool,
+	/// Stop once the file parsed, nothing is run.
+	pub no_execute: bool,
+	/// Report what the program does while it runs, see `trace::Tracer`.
+	pub trace: Option<TraceFormat>,
+}
+
+/// Kodu çalıştıracak motor
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Engine {
+	/// Ağaç yorumlayıcı (referans)
+	Tree,
+	/// Bayt kodu derleyicisi ve yığın tabanlı sanal makine
+	Vm,
+}
+
+/// Sets the built-in globals (terminal size etc.) on the root scope.
+pub fn DeclareGlobals(root_scope: usize, manager: &mut ScopeManager) {
+	if let Some((w, h)) = term_size::dimensions() {
+		manager.set_global(root_scope, "ekrangenişliği".to_string(), Object::from(w as f64));
+		manager.set_global(root_scope, "ekranyüksekliği".to_string(), Object::from(h as f64));
 	} else {
-		manager.push_code_to_scope(*current_scope_id, &instr.1);
+		manager.set_global(root_scope, "ekrangenişliği".to_string(), Object::from(0 as f64));
+		manager.set_global(root_scope, "ekranyüksekliği".to_string(), Object::from(0 as f64));
 	}
+}
 
-	Ok(())
+/// Turns a chumsky error over `tokens` into a report, mapping token indices back to their span in `source`.
+pub fn ParseError(filename: &str, source: &str, tokens: &[TokenData], e: &Simple<TokenData>) -> miette::Report {
+	let span = match (tokens.get(e.span().start), tokens.last()) {
+		(Some(token), _) => match tokens.get(e.span().end.saturating_sub(1)) {
+			Some(token_end) => token.span.to(token_end.span),
+			None => token.span,
+		},
+		// The file ended too early, point right after its last token.
+		(None, Some(last)) => Span { start: last.span.end, ..last.span },
+		(None, None) => Span::default(),
+	};
+	let src = NamedSource::new(filename, source.to_owned());
+	// The grammar only raises custom errors for lines indented under something that is not a block.
+	if let SimpleReason::Custom(_) = e.reason() {
+		return GirintiHatası { src, bad_bit: span.into() }.into();
+	}
+
+	let name = |x: &TokenData| match x.token {
+		TokenTable::Newline => "satır sonu".to_owned(),
+		TokenTable::Indent => "girinti".to_owned(),
+		TokenTable::Dedent => "girinti sonu".to_owned(),
+		_ => x.to_string(),
+	};
+	let mut expected = e.expected().map(|x| x.as_ref().map_or("dosya sonu".to_owned(), name)).collect::<Vec<_>>();
+	expected.sort();
+	TokenHatası {
+		src,
+		bad_bit: span.into(),
+		expected,
+		got: e.found().map_or("dosya sonu".to_owned(), name),
+	}
+	.into()
 }
 
-pub struct Runopts {
-	verbose: bool,
-	strict: bool,
+/// Turns a caught error into the object `yakala hata` binds to: its message, the name of its `ZenError` kind and line.
+pub fn ErrorObject(report: &miette::Report, line: usize) -> Object {
+	macro_rules! any {
+		($($t:ty),+) => { $(report.downcast_ref::<$t>().is_some())||+ };
+	}
+	let kind = if any!(DegiskenBulunamadı, FonksiyonBulunamadı) {
+		ZenError::NotDeclaredError
+	} else if any!(TipHatası) {
+		ZenError::TypeError
+	} else if any!(GirintiHatası, BoslukGirintisi) {
+		ZenError::IndentationError
+	} else if any!(TokenHatası, KapanmamisParantez, EgerOlmadanDegilse, DeneOlmadanYakala) {
+		ZenError::SyntaxError
+	} else if any!(CokFazlaArguman, EksikArguman) {
+		ZenError::ArgumentError
+	} else if any!(IndeksHatası) {
+		ZenError::IndexError
+	} else if any!(AnahtarBulunamadı) {
+		ZenError::KeyError
+	} else if any!(OzyinelemeHatası, YiginTukendi) {
+		ZenError::RecursionError
+	} else if any!(ModulBulunamadı, DonguselIceAktarma) {
+		ZenError::ImportError
+	} else if any!(DogrulamaBasarisiz, EsitDegil) {
+		ZenError::AssertionError
+	} else if any!(DonguDisinda, FonksiyonDisindaDondur, ErisilemeyenKod, KullanilmayanDegisken) {
+		ZenError::CheckError
+	} else if any!(FirlatilanHata) {
+		ZenError::GeneralError
+	} else {
+		ZenError::UnknownError
+	};
+
+	let mut error = Map { value: vec![] };
+	error.insert(Object::from("mesaj".to_owned()), Object::from(report.to_string()));
+	error.insert(Object::from("tür".to_owned()), Object::from(kind.name().to_owned()));
+	error.insert(Object::from("satır".to_owned()), Object::from(line as f64));
+	Object::Map(error)
 }
 
-pub fn index(input: &mut Vec<String>, full_source: String, verbose: bool, strict: bool, filename: &str) -> miette::Result<()> {
-	let mut manager = ScopeManager::new();
-	let root_scope = manager.create_scope(None, None);
-	let mut currentScope = root_scope;
-	let mut line_index = 0usize;
-	let opts = Runopts { verbose, strict };
+/// Tokenizes a whole file with `tokenize_file`, turning layout mistakes into reports.
+pub fn TokenizeSource(source: &str, filename: &str, file: usize, strict: bool) -> miette::Result<Vec<TokenData>> {
+	tokenize_file(source, file, strict).map_err(|e| LayoutErrorReport(filename, source, e))
+}
 
-	for line in input.iter_mut() {
-		line_index += 1;
-		for chunk in line.split(";") {
-			let raw_line_feed = tokenize(chunk);
-			// println!("{raw_line_feed:#?}");
-			if !raw_line_feed.is_all_ok() {
-				continue;
-			}
-			let line_feed_without_tabs = raw_line_feed.iter().filter(|x| x.token != TokenTable::Tab).cloned().collect::<Vec<_>>();
+/// The report for a file `tokenize_file` could not lay out.
+pub fn LayoutErrorReport(filename: &str, source: &str, e: LayoutError) -> miette::Report {
+	let src = NamedSource::new(filename, source.to_owned());
+	match e {
+		LayoutError::Spaces(span) => BoslukGirintisi { src, bad_bit: span.into() }.into(),
+		LayoutError::TooDeep(span) => GirintiHatası { src, bad_bit: span.into() }.into(),
+		LayoutError::Unclosed(span) => KapanmamisParantez { src, bad_bit: span.into() }.into(),
+	}
+}
 
-			if !line_feed_without_tabs.starts_with(&[TokenTable::Comment.asTokenData()]) && !line_feed_without_tabs.is_empty() {
-				match Parsers::parser().parse(line_feed_without_tabs.clone()) {
-					Ok(res) => {
-						match ProcessLine(
-							chunk.to_owned(),
-							full_source.clone(),
-							raw_line_feed,
-							res.clone(),
-							&mut currentScope,
-							&mut manager,
-							&opts,
-							(filename, line_index),
-						) {
-							Err(e) => {
-								return Err(e);
-							}
-							_ => {}
-						}
-					}
-					Err(e) => {
-						// Print chumsky parse error nicely
-						let mut out = String::new();
-						// Map parser error span to the original source span using tokenizer output
-						let (start, len) = if let Some(token) = line_feed_without_tabs.get(e[0].span().start) {
-							let start = token.span.start;
-							let end = if let Some(token_end) = line_feed_without_tabs.get(e[0].span().end.saturating_sub(1)) {
-								token_end.span.end
-							} else {
-								token.span.end
-							};
-							(start, end - start)
-						} else {
-							(e[0].span().start, e[0].span().end - e[0].span().start)
-						};
-						let report = miette::Report::new(TokenHatası {
-							src: NamedSource::new(filename, chunk.to_owned()),
-							bad_bit: SourceSpan::new(start.into(), len),
-							expected: e[0].expected().into_iter().map(|x| format!("{}", x.clone().unwrap())).collect::<Vec<_>>(),
-							got: e[0].found().map_or("".to_string(), |x| x.to_string()),
-						});
-						GraphicalReportHandler::new()
-							.with_width(80)
-							.render_report(&mut out, report.as_ref())
-							.unwrap();
-						eprintln!("Parse error at line {}:\n{}", line_index, out);
-					}
-				}
+/// Parses the tokens of a whole file into statements. Errors before the last one are printed, the last one is returned.
+pub fn ParseTokens(source: &str, filename: &str, tokens: Vec<TokenData>) -> miette::Result<Vec<Statement>> {
+	Parsers::parse_program(tokens.clone()).map_err(|mut errors| {
+		errors.sort_by_key(|x| x.span().start);
+		let last = errors.pop().expect("chumsky reports at least one error");
+		for e in &errors {
+			eprintln!("{:?}", ParseError(filename, source, &tokens, e));
+		}
+		ParseError(filename, source, &tokens, &last)
+	})
+	.and_then(|statements| {
+		CheckBlockOrder(source, filename, &statements)?;
+		Ok(statements)
+	})
+}
+
+/// `değilse` needs an `eğer` and `yakala` / `sonunda` a `dene` right before them, `BuildScopes` has nothing to attach
+/// them to otherwise.
+fn CheckBlockOrder(source: &str, filename: &str, statements: &[Statement]) -> miette::Result<()> {
+	let mut previous: Option<&InstructionEnum> = None;
+	for statement in statements {
+		let src = || NamedSource::new(filename, source.to_owned());
+		let bad_bit = statement.span.into();
+		match statement.instr {
+			InstructionEnum::ElifBlock { .. } | InstructionEnum::ElseBlock { .. }
+				if !matches!(previous, Some(InstructionEnum::IfBlock { .. } | InstructionEnum::ElifBlock { .. })) =>
+			{
+				return Err(EgerOlmadanDegilse { src: src(), bad_bit }.into());
+			}
+			InstructionEnum::CatchBlock { .. } if !matches!(previous, Some(InstructionEnum::TryBlock { .. })) => {
+				return Err(DeneOlmadanYakala { src: src(), bad_bit }.into());
 			}
+			InstructionEnum::FinallyBlock { .. } if !matches!(previous, Some(InstructionEnum::TryBlock { .. } | InstructionEnum::CatchBlock { .. })) => {
+				return Err(DeneOlmadanYakala { src: src(), bad_bit }.into());
+			}
+			_ => {}
 		}
+		CheckBlockOrder(source, filename, &statement.body)?;
+		previous = Some(&statement.instr);
 	}
+	Ok(())
+}
 
-	// println!("{:#?}\n-----------------------------------", manager.get_scope(0));
-	// println!("{:#?}\n-----------------------------------", manager.get_scope(1));
-	// println!("{:#?}\n-----------------------------------", manager.get_scope(2));
-	// println!("{:#?}\n-----------------------------------", manager.get_scope(3));
+/// Tokenizes and parses a whole file into statements. Nothing is declared or run yet, so a syntax error anywhere
+/// stops the file before any of it executes.
+pub fn ParseSource(source: &str, filename: &str, file: usize, strict: bool) -> miette::Result<Vec<Statement>> {
+	ParseTokens(source, filename, TokenizeSource(source, filename, file, strict)?)
+}
 
-	if let Some((w, h)) = term_size::dimensions() {
-		manager.set_global(root_scope, "ekrangenişliği".to_string(), Object::from(w as f64));
-		manager.set_global(root_scope, "ekranyüksekliği".to_string(), Object::from(h as f64));
-	} else {
-		manager.set_global(root_scope, "ekrangenişliği".to_string(), Object::from(0 as f64));
-		manager.set_global(root_scope, "ekranyüksekliği".to_string(), Object::from(0 as f64));
+/// Parses `full_source` and builds its scope tree under `root_scope`.
+pub fn LoadSource(full_source: &str, filename: &str, root_scope: usize, manager: &mut ScopeManager, opts: &Runopts) -> miette::Result<()> {
+	let file = manager.add_source(filename, full_source.to_owned());
+	let statements = ParseSource(full_source, filename, file, opts.strict)?;
+	BuildScopes(&statements, root_scope, manager);
+	Ok(())
+}
+
+/// Runs `içe aktar` from `scope_id`: the module is looked up relative to the importing file, executed the first
+/// time it is imported and then bound to `namespace`.
+pub fn ImportModule(path: &str, namespace: &str, scope_id: usize, manager: &mut ScopeManager, src: NamedSource<String>, span: SourceSpan) -> miette::Result<()> {
+	let importer = manager.source_name(manager.span().file).unwrap_or_default();
+	let base = Path::new(importer).parent().unwrap_or(Path::new(""));
+	let module_path = base.join(path);
+	let display_path = module_path.display().to_string();
+
+	let Ok(canonical) = module_path.canonicalize() else {
+		return Err(ModulBulunamadı { src, bad_bit: span, path: display_path })?;
+	};
+
+	if let Some(start) = manager.import_stack().iter().position(|x| *x == canonical) {
+		let chain = manager.import_stack()[start..]
+			.iter()
+			.chain(std::iter::once(&canonical))
+			.map(|x| x.file_name().map_or(x.display().to_string(), |name| name.to_string_lossy().into_owned()))
+			.collect::<Vec<_>>()
+			.join(" -> ");
+		return Err(DonguselIceAktarma { src, bad_bit: span, chain }.into());
+	}
+
+	let module_scope = match manager.get_loaded_module(&canonical) {
+		Some(module_scope) => module_scope,
+		None => {
+			let Ok(contents) = std::fs::read_to_string(&canonical) else {
+				return Err(ModulBulunamadı { src, bad_bit: span, path: display_path })?;
+			};
+			let opts = Runopts {
+				strict: manager.is_strict(),
+				..Default::default()
+			};
+			let module_scope = manager.create_scope(None, None);
+
+			manager.push_import(canonical.clone());
+			let outcome = LoadSource(&contents, &display_path, module_scope, manager, &opts).and_then(|_| {
+				ExecuteBlock(
+					module_scope,
+					manager,
+					NamedSource::new(&display_path, contents.clone()),
+					SourceSpan::new(0.into(), contents.len()),
+				)
+			});
+			manager.pop_import();
+			outcome?;
+
+			manager.set_loaded_module(canonical, module_scope);
+			module_scope
+		}
+	};
+
+	manager.declare_module(scope_id, namespace.to_owned(), module_scope);
+	Ok(())
+}
+
+pub fn index(full_source: String, opts: Runopts, recursion_limit: usize, filename: &str, engine: Engine) -> miette::Result<()> {
+	let mut manager = ScopeManager::new();
+	manager.set_recursion_limit(recursion_limit);
+	manager.set_strict(opts.strict);
+	RunSource(&mut manager, full_source, opts, filename, engine)
+}
+
+/// Runs a whole program on `manager`, which has no scopes yet. Embedders and tests use it to run a program on a
+/// manager they configured themselves, e.g. one that captures what the program prints.
+pub fn RunSource(manager: &mut ScopeManager, full_source: String, opts: Runopts, filename: &str, engine: Engine) -> miette::Result<()> {
+	let root_scope = manager.create_scope(None, None);
+
+	if let Ok(path) = Path::new(filename).canonicalize() {
+		manager.push_import(path);
+	}
+	let file = manager.add_source(filename, full_source.clone());
+	let tokens = TokenizeSource(&full_source, filename, file, opts.strict)?;
+	if opts.print_preprocess_output {
+		print!("{}", preprocessor::layout(&full_source, &tokens));
+	}
+	let statements = ParseTokens(&full_source, filename, tokens)?;
+	if opts.print_ast {
+		print!("{}", ast::render(&statements));
+	}
+	if opts.no_execute {
+		return Ok(());
 	}
 
-	ExecuteBlock(
-		root_scope,
-		&mut manager,
-		NamedSource::new(filename, full_source.clone()),
-		SourceSpan::new(0.into(), full_source.len()),
-	)?;
+	// The whole file parsed, only now are its scopes created.
+	BuildScopes(&statements, root_scope, manager);
+	DeclareGlobals(root_scope, manager);
+	if let Some(format) = opts.trace {
+		if engine == Engine::Vm {
+			return Err(miette!("--trace yalnızca ağaç yorumlayıcısıyla (--engine tree) kullanılabilir."));
+		}
+		manager.set_hook(Some(Rc::new(RefCell::new(Tracer::new(format)))));
+	}
+
+	match engine {
+		Engine::Tree => {
+			ExecuteBlock(
+				root_scope,
+				manager,
+				NamedSource::new(filename, full_source.clone()),
+				SourceSpan::new(0.into(), full_source.len()),
+			)?;
+		}
+		Engine::Vm => crate::vm::run(manager, root_scope)?,
+	}
 	Ok(())
 }
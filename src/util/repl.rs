@@ -0,0 +1,166 @@
+use std::{
+	panic::{AssertUnwindSafe, catch_unwind},
+	path::PathBuf,
+};
+
+use miette::{IntoDiagnostic, NamedSource, SourceSpan};
+use rustyline::{Cmd, DefaultEditor, KeyCode, KeyEvent, Modifiers, error::ReadlineError};
+
+use super::{
+	ScopeManager::ScopeManager,
+	process::{BuildScopes, DeclareGlobals, ExecuteBlock, ParseSource, Runopts},
+};
+use crate::{
+	features::tokenizer::{Atom, CheckTokenVec, Span, TokenData, TokenTable, YieldInstructionEnum, tokenize, tokenize_at},
+	parsers::Parsers,
+};
+
+const REPL_FILENAME: &str = "<repl>";
+const HISTORY_FILENAME: &str = ".zen_history";
+
+/// Interactive shell. Every entry is pushed into the same long-lived root scope, executed, and then
+/// removed from the scope's block so that only variables and functions survive between entries.
+pub fn start(strict: bool) -> miette::Result<()> {
+	let mut editor = DefaultEditor::new().into_diagnostic()?;
+	// Tab is the only indentation the grammar understands, so it has to insert a literal tab instead of completing.
+	editor.bind_sequence(KeyEvent(KeyCode::Tab, Modifiers::NONE), Cmd::Insert(1, "\t".to_owned()));
+
+	let history = std::env::var("HOME").ok().map(|home| PathBuf::from(home).join(HISTORY_FILENAME));
+	if let Some(path) = &history {
+		let _ = editor.load_history(path);
+	}
+
+	let mut manager = ScopeManager::new();
+	manager.set_strict(strict);
+	let root_scope = manager.create_scope(None, None);
+	DeclareGlobals(root_scope, &mut manager);
+	let opts = Runopts { strict, ..Default::default() };
+
+	println!("Zen etkileşimli kabuk. Blokları boş bir satırla bitirin, çıkmak için Ctrl+D kullanın.");
+
+	let mut entry: Vec<String> = Vec::new();
+	loop {
+		let prompt = if entry.is_empty() { ">>> " } else { "... " };
+		match editor.readline(prompt) {
+			Ok(line) => {
+				// Inside an unclosed `"""` literal or bracket every line, blank or not, belongs to the entry.
+				if Unfinished(&entry) {
+					entry.push(line);
+					if !Unfinished(&entry) && !OpensBlock(&entry[0]) {
+						RunEntry(&std::mem::take(&mut entry), root_scope, &mut manager, &opts);
+					}
+					continue;
+				}
+				if line.trim().is_empty() {
+					if !entry.is_empty() {
+						RunEntry(&std::mem::take(&mut entry), root_scope, &mut manager, &opts);
+					}
+					continue;
+				}
+				let _ = editor.add_history_entry(line.as_str());
+				if !entry.is_empty() || OpensBlock(&line) || Unfinished(std::slice::from_ref(&line)) {
+					entry.push(line);
+				} else {
+					RunEntry(&[line], root_scope, &mut manager, &opts);
+				}
+			}
+			Err(ReadlineError::Interrupted) => entry.clear(),
+			Err(ReadlineError::Eof) => break,
+			Err(err) => return Err(err).into_diagnostic(),
+		}
+	}
+
+	if let Some(path) = &history {
+		let _ = editor.save_history(path);
+	}
+	Ok(())
+}
+
+/// Drops tabs and comments, leaving the tokens `parse_line` and `parse_value` expect.
+pub fn CodeTokens(line_feed: &[TokenData]) -> Vec<TokenData> {
+	line_feed
+		.iter()
+		.filter(|x| !matches!(x.token, TokenTable::Tab | TokenTable::Comment))
+		.cloned()
+		.collect()
+}
+
+/// Returns true if the line starts a block (`eğer`, `fonksiyon`, `tekrarla`...) and needs continuation lines.
+fn OpensBlock(line: &str) -> bool {
+	line.split(";").any(|chunk| {
+		let line_feed = CodeTokens(&tokenize(chunk));
+		!line_feed.is_empty() && matches!(Parsers::parse_line(line_feed), Ok((output, _)) if output.indent)
+	})
+}
+
+/// Returns true if `lines` leave a `"""` literal or a bracket open.
+fn Unfinished(lines: &[String]) -> bool {
+	if lines.iter().map(|line| line.matches("\"\"\"").count()).sum::<usize>() % 2 == 1 {
+		return true;
+	}
+	let depth = tokenize(&lines.join("\n")).iter().fold(0i64, |depth, x| match x.token {
+		TokenTable::LPAREN | TokenTable::LSQBRACKET | TokenTable::LCRBRACKET => depth + 1,
+		TokenTable::RPAREN | TokenTable::RSQBRACKET | TokenTable::RCRBRACKET => depth - 1,
+		_ => depth,
+	});
+	depth > 0
+}
+
+fn RunEntry(lines: &[String], root_scope: usize, manager: &mut ScopeManager, opts: &Runopts) {
+	let source = lines.join("\n");
+	let file = manager.add_source(REPL_FILENAME, source.clone());
+
+	if lines.len() == 1 && EchoExpression(&lines[0], file, root_scope, manager) {
+		return;
+	}
+
+	let statements = match ParseSource(&source, REPL_FILENAME, file, opts.strict) {
+		Ok(statements) => statements,
+		Err(report) => {
+			eprintln!("{:?}", report);
+			return;
+		}
+	};
+	BuildScopes(&statements, root_scope, manager);
+
+	let span = SourceSpan::new(0.into(), source.len());
+	let outcome = catch_unwind(AssertUnwindSafe(|| {
+		ExecuteBlock(root_scope, manager, NamedSource::new(REPL_FILENAME, source.clone()), span)
+	}));
+	if let Ok(Err(report)) = outcome {
+		eprintln!("{:?}", report);
+	}
+
+	manager.clear_code(root_scope);
+}
+
+/// Evaluates the line and prints its value if it is a bare expression. Returns false if the line is not one.
+fn EchoExpression(line: &str, file: usize, root_scope: usize, manager: &mut ScopeManager) -> bool {
+	let line_feed = CodeTokens(&tokenize_at(line, file, 0, 1));
+	if line_feed.is_empty() || !line_feed.is_all_ok() {
+		return false;
+	}
+	let Ok(atom) = Parsers::parse_value(line_feed) else {
+		return false;
+	};
+	manager.set_location(Span::new(file, 0..line.len(), 1));
+
+	let outcome = catch_unwind(AssertUnwindSafe(|| match &atom {
+		// Calls are echoed only when the function actually returned something.
+		Atom::YieldInstruction(YieldInstructionEnum::CallFunction { name, args }) => {
+			let args = args
+				.iter()
+				.map(|x| Ok(x.evaluate(root_scope, manager)?.into()))
+				.collect::<miette::Result<Vec<_>>>()?;
+			let (src, span) = (manager.source(), manager.location());
+			manager.call_function(root_scope, name, args, src, span)
+		}
+		_ => Ok(Some(atom.resolve(root_scope, manager)?.evaluate(root_scope, manager)?)),
+	}));
+	match outcome {
+		Ok(Ok(Some(value))) => println!("{}", value),
+		Ok(Err(report)) => eprintln!("{:?}", report),
+		_ => {}
+	}
+	true
+}
@@ -0,0 +1,158 @@
+use crate::{
+	features::tokenizer::{AssignmentMethod, Span},
+	library::Types::{Object, ObjectType, TimeUnit},
+};
+
+/// How the values on the stack are turned into an `Accessor`, `{i}` takes one value and `{a:b}` up to two.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessorShape {
+	Index,
+	Slice { from: bool, to: bool },
+}
+
+impl AccessorShape {
+	/// Number of values the accessor pops from the stack.
+	pub fn arity(&self) -> usize {
+		match self {
+			AccessorShape::Index => 1,
+			AccessorShape::Slice { from, to } => *from as usize + *to as usize,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+	/// Pushes `constants[i]`.
+	Const(usize),
+	/// Pushes a local. Locals that were never set fall back to the globals (`ekrangenişliği`...).
+	Load(usize),
+	/// `ad.üye`: a member of the module imported as `ad`.
+	LoadDotted(String),
+	/// Pops the value and applies `method` to the local, `+=` and friends need the local to be set.
+	Assign(usize, AssignmentMethod),
+	Pop,
+
+	Add,
+	Sub,
+	Mul,
+	Div,
+	Mod,
+	Pow,
+	Less,
+	Greater,
+	LessEqual,
+	GreaterEqual,
+	Equal,
+	NotEqual,
+	/// Pops the container and the item, pushes whether the item is in it.
+	In,
+	Not,
+	/// Replaces the top of the stack with its truthiness.
+	ToBool,
+	/// Fails with a `TipHatası` unless the top of the stack can be a map key.
+	CheckKey,
+	/// Builds a map from the last `n` key, value pairs.
+	MakeMap(usize),
+	/// Joins the last `n` values into one text.
+	Format(usize),
+	/// Pops the accessor values and the container, pushes the element.
+	Get(AccessorShape),
+	/// `a{0}{1} += değer`, the accessor values are below the value on the stack.
+	SetPath { slot: usize, path: Vec<AccessorShape>, method: AssignmentMethod },
+	/// Fails with a `TipHatası` unless the top of the stack is a number, optionally rounding it down.
+	ExpectNumber { floor: bool },
+	/// `forceIntoNumber` on the top of the stack.
+	ToNumber,
+
+	Jump(usize),
+	/// Pops the condition and jumps if it is falsy.
+	JumpIfFalse(usize),
+	/// Pops the condition and jumps if it is truthy.
+	JumpIfTrue(usize),
+	/// `tekrarla`: leaves the loop once `counter` reaches `limit`, otherwise counts one more round.
+	RepeatNext { counter: usize, limit: usize, exit: usize },
+	/// `x ile y arasında`: leaves the loop once `current` reaches `end`, otherwise sets `var` to it.
+	ForNext { var: usize, current: usize, end: usize, step: usize, exit: usize },
+	/// Adds `step` to `current`.
+	ForStep { current: usize, step: usize },
+	/// Turns the popped value into the items a `her` loop walks over, maps give their keys.
+	IntoItems,
+	/// Leaves the loop once `index` is past the last item, otherwise sets `var` to the item. Fails if `step` is below one.
+	ForInNext { var: usize, items: usize, index: usize, step: usize, exit: usize },
+	/// Adds `step` to `index`.
+	ForInStep { index: usize, step: usize },
+
+	/// Calls whatever function the binding holds. `None` if no declaration was visible from the call site.
+	Call { binding: Option<usize>, argc: usize },
+	/// Calls `modül.fonksiyon` through the tree walker that ran the module.
+	CallModule { name: String, argc: usize },
+	/// Puts the function into its binding, the default arguments are on the stack in parameter order.
+	DefineFunction { function: usize, binding: usize, defaults: usize },
+	Import { path: String, namespace: String },
+	/// Runs a `dene` group. The blocks are chunks that share the current frame.
+	TryCatch {
+		try_block: usize,
+		catch_block: Option<usize>,
+		error_slot: Option<usize>,
+		finally_block: Option<usize>,
+		/// Where `durdur` / `devam et` coming out of the group jump. Without a loop around the group in this
+		/// chunk they end the chunk instead.
+		on_break: Option<usize>,
+		on_continue: Option<usize>,
+	},
+	Throw,
+	/// `doğrula`, pops the value and fails unless it is truthy.
+	Assert,
+	/// `eşit_olmalı`, pops two values and fails unless they are equal.
+	AssertEqual,
+	/// Pops the value and returns it from the chunk.
+	Return,
+	/// `durdur` with no loop in the chunk, ends the chunk with `BlockOutput::Break`.
+	Break,
+	/// `devam et` with no loop in the chunk, ends the chunk with `BlockOutput::Continue`.
+	Continue,
+
+	Print(usize),
+	PrintTypes(usize),
+	Input(Option<ObjectType>),
+	RandomNumber,
+	RandomLetter,
+	RandomBool,
+	Wait(TimeUnit),
+}
+
+/// A flat list of instructions. `spans[i]` is the code `code[i]` was compiled from, errors point there.
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+	pub code: Vec<Op>,
+	pub spans: Vec<Span>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+	pub name: String,
+	pub data_type: Option<ObjectType>,
+	pub has_default: bool,
+}
+
+/// A compiled `fonksiyon`. Its parameters take the first slots of the frame.
+#[derive(Debug, Clone)]
+pub struct Prototype {
+	pub name: String,
+	pub params: Vec<Parameter>,
+	pub chunk: usize,
+	pub layout: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+	pub constants: Vec<Object>,
+	pub chunks: Vec<Chunk>,
+	pub functions: Vec<Prototype>,
+	/// Number of function bindings, one per name and scope a `fonksiyon` is declared in.
+	pub bindings: usize,
+	/// Slot names of every frame, the top level and one per function.
+	pub layouts: Vec<Vec<String>>,
+	pub main: usize,
+	pub main_layout: usize,
+}
@@ -0,0 +1,655 @@
+use std::collections::{HashMap, HashSet};
+
+use super::bytecode::{AccessorShape, Chunk, Op, Parameter, Program, Prototype};
+use crate::{
+	features::tokenizer::{Accessor, AssignmentMethod, Atom, InstructionEnum, Span, TokenTable, YieldInstructionEnum},
+	library::Types::{Object, ObjectType, RandomizerType},
+	parsers::Parsers::Expression,
+	util::ScopeManager::ScopeManager,
+};
+
+/// Name given to the slots the compiler adds for loop counters. It can't collide with an identifier.
+const HIDDEN_SLOT: &str = "<döngü>";
+
+/// Jumps out of the loop that is being compiled, patched once the loop's exit and next round are known.
+#[derive(Default)]
+struct Loop {
+	breaks: Vec<usize>,
+	continues: Vec<usize>,
+}
+
+#[derive(Default)]
+struct ChunkBuilder {
+	chunk: Chunk,
+	loops: Vec<Loop>,
+}
+
+impl ChunkBuilder {
+	fn here(&self) -> usize {
+		self.chunk.code.len()
+	}
+
+	/// Points the jump at `at` to `target`.
+	fn patch(&mut self, at: usize, target: usize) {
+		match &mut self.chunk.code[at] {
+			Op::Jump(x) | Op::JumpIfFalse(x) | Op::JumpIfTrue(x) => *x = target,
+			Op::RepeatNext { exit, .. } | Op::ForNext { exit, .. } | Op::ForInNext { exit, .. } => *exit = target,
+			other => unreachable!("{other:?} is not a jump"),
+		}
+	}
+
+	fn close_loop(&mut self, exit: usize, next: usize) {
+		let Loop { breaks, continues } = self.loops.pop().unwrap();
+		for at in breaks {
+			match &mut self.chunk.code[at] {
+				Op::TryCatch { on_break, .. } => *on_break = Some(exit),
+				_ => self.patch(at, exit),
+			}
+		}
+		for at in continues {
+			match &mut self.chunk.code[at] {
+				Op::TryCatch { on_continue, .. } => *on_continue = Some(next),
+				_ => self.patch(at, next),
+			}
+		}
+	}
+}
+
+/// Compiles the scope tree `BuildScopes` built into a `Program`. Blocks of loops and conditions are inlined into
+/// the chunk around them, function bodies and the blocks of a `dene` group get chunks of their own.
+pub struct Compiler<'a> {
+	manager: &'a ScopeManager,
+	program: Program,
+	/// Binding of every function, by the scope it is declared in and its name.
+	bindings: HashMap<(usize, String), usize>,
+	/// Layout of the frame that is being compiled.
+	layout: usize,
+	/// Names declared in the `içinde dolan` bodies that are being compiled, innermost last. Like the scope the tree
+	/// walker gives such a body, they can't be seen once the loop is over.
+	blocks: Vec<HashMap<String, usize>>,
+	/// Slots of the current layout that belong to one of those bodies, names outside of it never resolve to them.
+	block_slots: HashSet<usize>,
+	/// Scope whose block is being compiled, calls inside expressions are resolved from it.
+	scope: usize,
+	span: Span,
+}
+
+impl<'a> Compiler<'a> {
+	pub fn compile(manager: &'a ScopeManager, root_scope: usize) -> Program {
+		let mut compiler = Compiler {
+			manager,
+			program: Program::default(),
+			bindings: HashMap::new(),
+			layout: 0,
+			blocks: Vec::new(),
+			block_slots: HashSet::new(),
+			scope: root_scope,
+			span: Span::default(),
+		};
+		compiler.declare_functions(root_scope);
+		compiler.program.layouts.push(Vec::new());
+		compiler.program.main = compiler.block(root_scope);
+		compiler.program
+	}
+
+	/// Gives every `fonksiyon` in the tree a binding up front, so calls can be resolved before the declaration
+	/// they refer to has been compiled.
+	fn declare_functions(&mut self, root_scope: usize) {
+		for scope_id in std::iter::once(root_scope).chain(self.manager.get_children_tree(root_scope)) {
+			let Some(scope) = self.manager.get_scope(scope_id) else { continue };
+			for (instr, _) in &scope.block {
+				if let InstructionEnum::Function { name, .. } = instr
+					&& !self.bindings.contains_key(&(scope_id, name.clone()))
+				{
+					self.bindings.insert((scope_id, name.clone()), self.program.bindings);
+					self.program.bindings += 1;
+				}
+			}
+		}
+	}
+
+	/// The binding a call from `scope_id` refers to, found the way `ScopeManager::get_function` looks it up.
+	fn binding(&self, mut scope_id: usize, name: &str) -> Option<usize> {
+		loop {
+			if let Some(binding) = self.bindings.get(&(scope_id, name.to_owned())) {
+				return Some(*binding);
+			}
+			scope_id = self.manager.get_parent(scope_id)?;
+		}
+	}
+
+	fn emit(&self, out: &mut ChunkBuilder, op: Op) -> usize {
+		out.chunk.code.push(op);
+		out.chunk.spans.push(self.span);
+		out.chunk.code.len() - 1
+	}
+
+	fn constant(&mut self, value: Object) -> usize {
+		self.program.constants.push(value);
+		self.program.constants.len() - 1
+	}
+
+	/// Slot `name` refers to here, a name seen for the first time is declared in the innermost loop body.
+	fn slot(&mut self, name: &str) -> usize {
+		if let Some(slot) = self.blocks.iter().rev().find_map(|x| x.get(name)) {
+			return *slot;
+		}
+		let layout = &self.program.layouts[self.layout];
+		match (0..layout.len()).find(|x| layout[*x] == name && !self.block_slots.contains(x)) {
+			Some(slot) => slot,
+			None => self.declare(name),
+		}
+	}
+
+	/// Gives `name` a new slot, in the innermost loop body if there is one.
+	fn declare(&mut self, name: &str) -> usize {
+		let layout = &mut self.program.layouts[self.layout];
+		layout.push(name.to_owned());
+		let slot = layout.len() - 1;
+		if let Some(block) = self.blocks.last_mut() {
+			block.insert(name.to_owned(), slot);
+			self.block_slots.insert(slot);
+		}
+		slot
+	}
+
+	fn hidden_slot(&mut self) -> usize {
+		let layout = &mut self.program.layouts[self.layout];
+		layout.push(HIDDEN_SLOT.to_owned());
+		layout.len() - 1
+	}
+
+	/// Compiles the scope into a chunk of its own and returns its index.
+	fn block(&mut self, scope_id: usize) -> usize {
+		let mut out = ChunkBuilder::default();
+		self.statements(&mut out, scope_id);
+		self.program.chunks.push(out.chunk);
+		self.program.chunks.len() - 1
+	}
+
+	fn statements(&mut self, out: &mut ChunkBuilder, scope_id: usize) {
+		let manager = self.manager;
+		let Some(scope) = manager.get_scope(scope_id) else { return };
+		let outer = self.scope;
+		self.scope = scope_id;
+		for (instr, span) in &scope.block {
+			self.span = *span;
+			self.statement(out, scope_id, instr);
+		}
+		self.scope = outer;
+	}
+
+	/// Compiles `f(...)` into a push of the result, calls that return nothing push a falsy value.
+	fn call<'e>(&mut self, out: &mut ChunkBuilder, scope_id: usize, name: &str, args: impl ExactSizeIterator<Item = &'e Expression>) {
+		let argc = args.len();
+		for arg in args {
+			self.expression(out, arg);
+		}
+		if name.contains('.') {
+			self.emit(out, Op::CallModule { name: name.to_owned(), argc });
+		} else {
+			let binding = self.binding(scope_id, name);
+			self.emit(out, Op::Call { binding, argc });
+		}
+	}
+
+	/// Compiles `value` and checks that it is a number, errors point at `value` if it has a span.
+	fn number(&mut self, out: &mut ChunkBuilder, value: &Atom, floor: bool) {
+		let span = self.span;
+		self.atom(out, value);
+		self.span = value.span().unwrap_or(span);
+		self.emit(out, Op::ExpectNumber { floor });
+		self.span = span;
+	}
+
+	fn store(&mut self, out: &mut ChunkBuilder, slot: usize) {
+		self.emit(out, Op::Assign(slot, AssignmentMethod::Set));
+	}
+
+	fn statement(&mut self, out: &mut ChunkBuilder, scope_id: usize, instr: &InstructionEnum) {
+		let span = self.span;
+		match instr {
+			InstructionEnum::Print(values) | InstructionEnum::Type(values) => {
+				for value in values {
+					self.atom(out, value);
+				}
+				match instr {
+					InstructionEnum::Print(_) => self.emit(out, Op::Print(values.len())),
+					_ => self.emit(out, Op::PrintTypes(values.len())),
+				};
+			}
+			InstructionEnum::VariableDeclaration(name, value, method) => {
+				self.atom(out, value);
+				let slot = self.slot(name);
+				self.emit(out, Op::Assign(slot, method.clone()));
+			}
+			InstructionEnum::IndexAssignment { name, path, value, method } => {
+				let path = path.iter().map(|x| self.accessor(out, x)).collect();
+				self.atom(out, value);
+				let slot = self.slot(name);
+				self.emit(out, Op::SetPath { slot, path, method: method.clone() });
+			}
+			InstructionEnum::WhileTrue { scope_pointer } => {
+				let top = out.here();
+				out.loops.push(Loop::default());
+				self.statements(out, *scope_pointer);
+				self.span = span;
+				self.emit(out, Op::Jump(top));
+				out.close_loop(out.here(), top);
+			}
+			InstructionEnum::RepeatUntil { condition, scope_pointer } => {
+				let top = out.here();
+				out.loops.push(Loop::default());
+				self.statements(out, *scope_pointer);
+				self.span = span;
+				let next = out.here();
+				self.atom(out, condition);
+				self.emit(out, Op::JumpIfFalse(top));
+				out.close_loop(out.here(), next);
+			}
+			InstructionEnum::RepeatWhile { condition, scope_pointer } => {
+				let top = out.here();
+				self.atom(out, condition);
+				let exit = self.emit(out, Op::JumpIfTrue(0));
+				out.loops.push(Loop::default());
+				self.statements(out, *scope_pointer);
+				self.span = span;
+				self.emit(out, Op::Jump(top));
+				out.patch(exit, out.here());
+				out.close_loop(out.here(), top);
+			}
+			InstructionEnum::Repeat { repeat_count, scope_pointer } => {
+				let (limit, counter) = (self.hidden_slot(), self.hidden_slot());
+				self.number(out, repeat_count, true);
+				self.store(out, limit);
+				let zero = self.constant(Object::from(0f64));
+				self.emit(out, Op::Const(zero));
+				self.store(out, counter);
+
+				let top = self.emit(out, Op::RepeatNext { counter, limit, exit: 0 });
+				out.loops.push(Loop::default());
+				self.statements(out, *scope_pointer);
+				self.span = span;
+				self.emit(out, Op::Jump(top));
+				out.patch(top, out.here());
+				out.close_loop(out.here(), top);
+			}
+			InstructionEnum::For {
+				from,
+				to,
+				step,
+				name,
+				scope_pointer,
+			} => {
+				let (current, end, step_slot) = (self.hidden_slot(), self.hidden_slot(), self.hidden_slot());
+				self.number(out, from, true);
+				self.store(out, current);
+				self.number(out, to, true);
+				self.store(out, end);
+				match step {
+					Some(step) => self.number(out, step, true),
+					None => {
+						let one = self.constant(Object::from(1f64));
+						self.emit(out, Op::Const(one));
+					}
+				}
+				self.store(out, step_slot);
+
+				let var = self.slot(name);
+				let top = self.emit(out, Op::ForNext {
+					var,
+					current,
+					end,
+					step: step_slot,
+					exit: 0,
+				});
+				out.loops.push(Loop::default());
+				self.statements(out, *scope_pointer);
+				self.span = span;
+				let next = self.emit(out, Op::ForStep { current, step: step_slot });
+				self.emit(out, Op::Jump(top));
+				out.patch(top, out.here());
+				out.close_loop(out.here(), next);
+			}
+			InstructionEnum::ForIn { name, step, varname, scope_pointer } => {
+				let (items, index, step_slot) = (self.hidden_slot(), self.hidden_slot(), self.hidden_slot());
+				self.load(out, name);
+				self.emit(out, Op::IntoItems);
+				self.store(out, items);
+				match step {
+					Some(step) => {
+						self.atom(out, step);
+						self.emit(out, Op::ToNumber);
+					}
+					None => {
+						let one = self.constant(Object::from(1f64));
+						self.emit(out, Op::Const(one));
+					}
+				}
+				self.store(out, step_slot);
+				let zero = self.constant(Object::from(0f64));
+				self.emit(out, Op::Const(zero));
+				self.store(out, index);
+
+				// The loop variable always belongs to the body, even if the name is taken outside of it.
+				self.blocks.push(HashMap::new());
+				let var = self.declare(varname);
+				let top = self.emit(out, Op::ForInNext {
+					var,
+					items,
+					index,
+					step: step_slot,
+					exit: 0,
+				});
+				out.loops.push(Loop::default());
+				self.statements(out, *scope_pointer);
+				self.blocks.pop();
+				self.span = span;
+				let next = self.emit(out, Op::ForInStep { index, step: step_slot });
+				self.emit(out, Op::Jump(top));
+				out.patch(top, out.here());
+				out.close_loop(out.here(), next);
+			}
+			InstructionEnum::Condition(condition) => {
+				let mut ends = Vec::new();
+				for branch in std::iter::once(&condition.If).chain(&condition.Elif) {
+					self.span = span;
+					self.atom(out, &branch.condition);
+					let skip = self.emit(out, Op::JumpIfFalse(0));
+					self.statements(out, branch.scope_pointer);
+					self.span = span;
+					ends.push(self.emit(out, Op::Jump(0)));
+					out.patch(skip, out.here());
+				}
+				if condition.Else.scope_pointer != 0 {
+					self.statements(out, condition.Else.scope_pointer);
+				}
+				for at in ends {
+					out.patch(at, out.here());
+				}
+			}
+			InstructionEnum::Function { name, args, scope_pointer } => {
+				let mut params = Vec::new();
+				let mut defaults = 0;
+				for arg in args {
+					if let Some(default) = &arg.default_value {
+						self.expression(out, default);
+						defaults += 1;
+					}
+					params.push(Parameter {
+						name: arg.name.clone(),
+						data_type: arg.data_type.as_ref().map(|token| match token.token {
+							TokenTable::KeywordSayı => ObjectType::Number,
+							TokenTable::KeywordMetin => ObjectType::Text,
+							TokenTable::KeywordMantıksal => ObjectType::Boolean,
+							_ => unreachable!("parameter() only accepts the main types"),
+						}),
+						has_default: arg.default_value.is_some(),
+					});
+				}
+
+				let outer_layout = self.layout;
+				let outer_blocks = (std::mem::take(&mut self.blocks), std::mem::take(&mut self.block_slots));
+				self.program.layouts.push(params.iter().map(|x| x.name.clone()).collect());
+				self.layout = self.program.layouts.len() - 1;
+				let chunk = self.block(*scope_pointer);
+				let layout = self.layout;
+				self.layout = outer_layout;
+				(self.blocks, self.block_slots) = outer_blocks;
+				self.span = span;
+
+				self.program.functions.push(Prototype {
+					name: name.clone(),
+					params,
+					chunk,
+					layout,
+				});
+				let function = self.program.functions.len() - 1;
+				let binding = self.bindings[&(scope_id, name.clone())];
+				self.emit(out, Op::DefineFunction { function, binding, defaults });
+			}
+			InstructionEnum::Import { path, namespace } => {
+				self.emit(out, Op::Import {
+					path: path.clone(),
+					namespace: namespace.clone(),
+				});
+			}
+			InstructionEnum::CallFunction { name, args } => {
+				let argc = args.len();
+				for arg in args {
+					self.atom(out, arg);
+				}
+				if name.contains('.') {
+					self.emit(out, Op::CallModule { name: name.clone(), argc });
+				} else {
+					let binding = self.binding(scope_id, name);
+					self.emit(out, Op::Call { binding, argc });
+				}
+				// Statement level calls are only run for their side effects, the return value is dropped.
+				self.emit(out, Op::Pop);
+			}
+			InstructionEnum::TryCatch(structure) => {
+				let try_block = self.block(structure.Try);
+				let catch_block = (structure.Catch != 0).then(|| self.block(structure.Catch));
+				let error_slot = match (catch_block, &structure.ErrorName) {
+					(Some(_), Some(name)) => Some(self.slot(name)),
+					_ => None,
+				};
+				let finally_block = (structure.Finally != 0).then(|| self.block(structure.Finally));
+				self.span = span;
+				let at = self.emit(out, Op::TryCatch {
+					try_block,
+					catch_block,
+					error_slot,
+					finally_block,
+					on_break: None,
+					on_continue: None,
+				});
+				if let Some(current) = out.loops.last_mut() {
+					current.breaks.push(at);
+					current.continues.push(at);
+				}
+			}
+			InstructionEnum::Throw(value) => {
+				self.atom(out, value);
+				self.emit(out, Op::Throw);
+			}
+			InstructionEnum::Assert(value) => {
+				self.atom(out, value);
+				self.emit(out, Op::Assert);
+			}
+			InstructionEnum::AssertEqual(left, right) => {
+				self.atom(out, left);
+				self.atom(out, right);
+				self.emit(out, Op::AssertEqual);
+			}
+			InstructionEnum::Break => {
+				if out.loops.is_empty() {
+					self.emit(out, Op::Break);
+				} else {
+					let at = self.emit(out, Op::Jump(0));
+					out.loops.last_mut().unwrap().breaks.push(at);
+				}
+			}
+			InstructionEnum::Continue => {
+				if out.loops.is_empty() {
+					self.emit(out, Op::Continue);
+				} else {
+					let at = self.emit(out, Op::Jump(0));
+					out.loops.last_mut().unwrap().continues.push(at);
+				}
+			}
+			InstructionEnum::Return(value) => {
+				self.atom(out, value);
+				self.emit(out, Op::Return);
+			}
+			InstructionEnum::Wait { amount, unit } => {
+				self.number(out, amount, false);
+				self.emit(out, Op::Wait(unit.clone()));
+			}
+			// Block headers are turned into `Condition`, `TryCatch` or a block pointer by `BuildScopes`.
+			_ => {}
+		}
+	}
+
+	fn accessor(&mut self, out: &mut ChunkBuilder, accessor: &Accessor) -> AccessorShape {
+		match accessor {
+			Accessor::Index(index) => {
+				self.expression(out, index);
+				AccessorShape::Index
+			}
+			Accessor::Slice(from, to) => {
+				for bound in [from, to].into_iter().flatten() {
+					self.expression(out, bound);
+				}
+				AccessorShape::Slice {
+					from: from.is_some(),
+					to: to.is_some(),
+				}
+			}
+		}
+	}
+
+	fn load(&mut self, out: &mut ChunkBuilder, name: &str) {
+		if name.contains('.') {
+			self.emit(out, Op::LoadDotted(name.to_owned()));
+		} else {
+			let slot = self.slot(name);
+			self.emit(out, Op::Load(slot));
+		}
+	}
+
+	fn atom(&mut self, out: &mut ChunkBuilder, atom: &Atom) {
+		match atom {
+			Atom::Expression(expr) => self.expression(out, expr),
+			Atom::YieldInstruction(instr) => self.yield_instruction(out, instr),
+		}
+	}
+
+	fn yield_instruction(&mut self, out: &mut ChunkBuilder, instr: &YieldInstructionEnum) {
+		match instr {
+			YieldInstructionEnum::Input { quote, _type } => {
+				self.expression(out, quote);
+				let data_type = _type.as_ref().and_then(|x| match x.token {
+					TokenTable::KeywordSayı => Some(ObjectType::Number),
+					TokenTable::KeywordMetin => Some(ObjectType::Text),
+					TokenTable::KeywordMantıksal => Some(ObjectType::Boolean),
+					_ => None,
+				});
+				self.emit(out, Op::Input(data_type));
+			}
+			YieldInstructionEnum::Random { method, span } => match method {
+				RandomizerType::Number => {
+					match span {
+						Some((from, to)) => {
+							self.expression(out, from);
+							self.expression(out, to);
+						}
+						None => {
+							let (from, to) = (self.constant(Object::from(0f64)), self.constant(Object::from(1f64)));
+							self.emit(out, Op::Const(from));
+							self.emit(out, Op::Const(to));
+						}
+					}
+					self.emit(out, Op::RandomNumber);
+				}
+				RandomizerType::Letter => {
+					self.emit(out, Op::RandomLetter);
+				}
+				RandomizerType::Boolean { chance } => {
+					self.expression(out, chance);
+					self.emit(out, Op::RandomBool);
+				}
+			},
+			YieldInstructionEnum::CallFunction { name, args } => {
+				self.call(out, self.scope, name, args.iter());
+			}
+			YieldInstructionEnum::RandomVar(name) => {
+				// Not implemented by the tree walker either, which prints this and yields a falsy value.
+				let message = self.constant(Object::from(format!("Random var instruction: {name}")));
+				self.emit(out, Op::Const(message));
+				self.emit(out, Op::Print(1));
+				let falsy = self.constant(Object::from(false));
+				self.emit(out, Op::Const(falsy));
+			}
+			YieldInstructionEnum::Index(name, path) => {
+				self.load(out, name);
+				for accessor in path {
+					let shape = self.accessor(out, accessor);
+					self.emit(out, Op::Get(shape));
+				}
+			}
+		}
+	}
+
+	fn binary(&mut self, out: &mut ChunkBuilder, lhs: &Expression, rhs: &Expression, op: Op) {
+		self.expression(out, lhs);
+		self.expression(out, rhs);
+		self.emit(out, op);
+	}
+
+	fn expression(&mut self, out: &mut ChunkBuilder, expr: &Expression) {
+		match expr {
+			Expression::Spanned(inner, span) => {
+				let outer = self.span;
+				self.span = *span;
+				self.expression(out, inner);
+				self.span = outer;
+			}
+			Expression::Value(value) => match &**value {
+				Object::Variable(name) => self.load(out, name),
+				value => {
+					let index = self.constant(value.clone());
+					self.emit(out, Op::Const(index));
+				}
+			},
+			Expression::Not(inner) => {
+				self.expression(out, inner);
+				self.emit(out, Op::Not);
+			}
+			Expression::Add(lhs, rhs) => self.binary(out, lhs, rhs, Op::Add),
+			Expression::Sub(lhs, rhs) => self.binary(out, lhs, rhs, Op::Sub),
+			Expression::Mul(lhs, rhs) => self.binary(out, lhs, rhs, Op::Mul),
+			Expression::Div(lhs, rhs) => self.binary(out, lhs, rhs, Op::Div),
+			Expression::Mod(lhs, rhs) => self.binary(out, lhs, rhs, Op::Mod),
+			Expression::Pow(lhs, rhs) => self.binary(out, lhs, rhs, Op::Pow),
+			Expression::LessThan(lhs, rhs) => self.binary(out, lhs, rhs, Op::Less),
+			Expression::GreaterThan(lhs, rhs) => self.binary(out, lhs, rhs, Op::Greater),
+			Expression::LessThanOrEqual(lhs, rhs) => self.binary(out, lhs, rhs, Op::LessEqual),
+			Expression::GreaterThanOrEqual(lhs, rhs) => self.binary(out, lhs, rhs, Op::GreaterEqual),
+			Expression::Equal(lhs, rhs) => self.binary(out, lhs, rhs, Op::Equal),
+			Expression::NotEqual(lhs, rhs) => self.binary(out, lhs, rhs, Op::NotEqual),
+			Expression::In(lhs, rhs) => self.binary(out, lhs, rhs, Op::In),
+			// Short-circuiting: the right hand side is only evaluated when it can change the result.
+			Expression::And(lhs, rhs) | Expression::Or(lhs, rhs) => {
+				let is_and = matches!(expr, Expression::And(..));
+				self.expression(out, lhs);
+				let short = if is_and { self.emit(out, Op::JumpIfFalse(0)) } else { self.emit(out, Op::JumpIfTrue(0)) };
+				self.expression(out, rhs);
+				self.emit(out, Op::ToBool);
+				let end = self.emit(out, Op::Jump(0));
+				out.patch(short, out.here());
+				let result = self.constant(Object::from(!is_and));
+				self.emit(out, Op::Const(result));
+				out.patch(end, out.here());
+			}
+			Expression::Map(entries) => {
+				for (key, value) in entries {
+					self.expression(out, key);
+					let span = self.span;
+					self.span = key.span().unwrap_or(span);
+					self.emit(out, Op::CheckKey);
+					self.span = span;
+					self.expression(out, value);
+				}
+				self.emit(out, Op::MakeMap(entries.len()));
+			}
+			Expression::Format(parts) => {
+				for part in parts {
+					self.expression(out, part);
+				}
+				self.emit(out, Op::Format(parts.len()));
+			}
+		}
+	}
+}
@@ -0,0 +1,577 @@
+use miette::{NamedSource, SourceSpan};
+use num::pow::Pow;
+use rand::Rng;
+
+use super::bytecode::{AccessorShape, Op, Program};
+use crate::{
+	Input,
+	features::{
+		ast,
+		tokenizer::{Accessor, AssignmentMethod, Span},
+	},
+	library::{
+		Error::{
+			CokFazlaArguman, DegiskenBulunamadı, DogrulamaBasarisiz, EksikArguman, EsitDegil, FirlatilanHata, FonksiyonBulunamadı, OzyinelemeHatası,
+			TipHatası, YiginTukendi,
+		},
+		Types::{Array, LETTERARRAY, Map, Object, ObjectType},
+	},
+	parsers::Parsers::Expression,
+	util::{
+		ScopeManager::{ScopeManager, StackExhausted},
+		process::{BlockOutput, ErrorObject, ImportModule},
+	},
+};
+
+/// Locals of one function call, or of the top level.
+pub struct Frame {
+	slots: Vec<Option<Object>>,
+	layout: usize,
+}
+
+impl Frame {
+	pub fn new(program: &Program, layout: usize) -> Self {
+		Self {
+			slots: vec![None; program.layouts[layout].len()],
+			layout,
+		}
+	}
+
+	fn number(&self, slot: usize) -> f64 {
+		match &self.slots[slot] {
+			Some(Object::Number(number)) => number.value,
+			_ => 0.0,
+		}
+	}
+
+	fn set_number(&mut self, slot: usize, value: f64) {
+		self.slots[slot] = Some(Object::from(value));
+	}
+}
+
+/// A declared function and the values of its default arguments, evaluated where it was declared.
+struct Binding {
+	function: usize,
+	defaults: Vec<Option<Object>>,
+}
+
+/// Runs a compiled `Program`. The scope manager is still used for the globals, the sources diagnostics point
+/// into and for modules, which are loaded and run by the tree walker.
+pub struct Machine<'a> {
+	program: &'a Program,
+	manager: &'a mut ScopeManager,
+	root_scope: usize,
+	bindings: Vec<Option<Binding>>,
+	depth: usize,
+}
+
+impl<'a> Machine<'a> {
+	pub fn new(program: &'a Program, manager: &'a mut ScopeManager, root_scope: usize) -> Self {
+		Self {
+			program,
+			manager,
+			root_scope,
+			bindings: (0..program.bindings).map(|_| None).collect(),
+			depth: 0,
+		}
+	}
+
+	pub fn run(&mut self) -> miette::Result<()> {
+		let mut frame = Frame::new(self.program, self.program.main_layout);
+		self.execute(self.program.main, &mut frame)?;
+		Ok(())
+	}
+
+	/// Moves the location to `span` and returns what a diagnostic raised there needs.
+	fn locate(&mut self, span: Span) -> (NamedSource<String>, SourceSpan) {
+		self.manager.set_location(span);
+		(self.manager.source_of(span.file), span.into())
+	}
+
+	fn missing_variable(&mut self, span: Span) -> miette::Report {
+		let (src, bad_bit) = self.locate(span);
+		DegiskenBulunamadı { src, bad_bit }.into()
+	}
+
+	fn global(&self, name: &str) -> Option<Object> {
+		self.manager.get_var(self.root_scope, name)
+	}
+
+	/// `ad.üye`, a member of the module imported as `ad`.
+	fn dotted(&self, name: &str) -> Option<Object> {
+		let (owner, _) = name.split_once('.')?;
+		self.manager.get_module(self.root_scope, owner)?;
+		self.manager.get_var(self.root_scope, name)
+	}
+
+	/// Value a compound assignment (`+=`...) to `slot` starts from.
+	fn previous(&self, frame: &Frame, slot: usize) -> Option<Object> {
+		let layout = &self.program.layouts[frame.layout];
+		let name = &layout[slot];
+		match name.split_once('.') {
+			Some(_) => self.dotted(name),
+			None => frame.slots[slot].clone().or_else(|| self.global(name)),
+		}
+	}
+
+	fn accessor(shape: AccessorShape, mut values: std::vec::IntoIter<Object>) -> Accessor<Object> {
+		match shape {
+			AccessorShape::Index => Accessor::Index(values.next().unwrap()),
+			AccessorShape::Slice { from, to } => Accessor::Slice(from.then(|| values.next().unwrap()), to.then(|| values.next().unwrap())),
+		}
+	}
+
+	fn execute(&mut self, chunk: usize, frame: &mut Frame) -> miette::Result<BlockOutput> {
+		let program = self.program;
+		let chunk = &program.chunks[chunk];
+		let mut stack: Vec<Object> = Vec::new();
+		let mut pc = 0;
+
+		while let Some(op) = chunk.code.get(pc) {
+			let span = chunk.spans[pc];
+			pc += 1;
+			match op {
+				Op::Const(index) => stack.push(program.constants[*index].clone()),
+				Op::Load(slot) => {
+					let value = match &frame.slots[*slot] {
+						Some(value) => value.clone(),
+						None => match self.global(&program.layouts[frame.layout][*slot]) {
+							Some(value) => value,
+							None => return Err(self.missing_variable(span)),
+						},
+					};
+					stack.push(value);
+				}
+				Op::LoadDotted(name) => match self.dotted(name) {
+					Some(value) => stack.push(value),
+					None => return Err(self.missing_variable(span)),
+				},
+				Op::Assign(slot, method) => {
+					let value = stack.pop().unwrap();
+					let value = match method {
+						AssignmentMethod::Set => value,
+						_ => match self.previous(frame, *slot) {
+							Some(previous) => method.combine(previous, value),
+							None => return Err(self.missing_variable(span)),
+						},
+					};
+					frame.slots[*slot] = Some(value);
+				}
+				Op::Pop => {
+					stack.pop();
+				}
+
+				Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Mod | Op::Pow => {
+					let rhs = stack.pop().unwrap();
+					let lhs = stack.pop().unwrap();
+					stack.push(match op {
+						Op::Add => lhs + rhs,
+						Op::Sub => lhs - rhs,
+						Op::Mul => lhs * rhs,
+						Op::Div => lhs / rhs,
+						Op::Mod => lhs % rhs,
+						_ => lhs.pow(rhs),
+					});
+				}
+				Op::Less | Op::Greater | Op::LessEqual | Op::GreaterEqual | Op::Equal | Op::NotEqual => {
+					let rhs = stack.pop().unwrap();
+					let lhs = stack.pop().unwrap();
+					stack.push(Object::from(match op {
+						Op::Less => lhs < rhs,
+						Op::Greater => lhs > rhs,
+						Op::LessEqual => lhs <= rhs,
+						Op::GreaterEqual => lhs >= rhs,
+						Op::Equal => lhs == rhs,
+						_ => lhs != rhs,
+					}));
+				}
+				Op::In => {
+					let container = stack.pop().unwrap();
+					let item = stack.pop().unwrap();
+					let found = match &container {
+						Object::Map(map) => map.get(&item).is_some(),
+						Object::Array(list) => list.value.contains(&item),
+						_ => {
+							let (src, bad_bit) = self.locate(span);
+							container.contains(&item, src, bad_bit)?
+						}
+					};
+					stack.push(Object::from(found));
+				}
+				Op::Not => {
+					let value = stack.pop().unwrap();
+					stack.push(Object::from(!value.isTruthy()));
+				}
+				Op::ToBool => {
+					let value = stack.pop().unwrap();
+					stack.push(Object::from(value.isTruthy()));
+				}
+				Op::CheckKey => {
+					let key = stack.last().unwrap();
+					if !Map::is_valid_key(key) {
+						let found = format!("{:?}", key);
+						let (src, bad_bit) = self.locate(span);
+						return Err(TipHatası::expected(
+							"Sözlük anahtarlarının bir Sayı veya Metin olması bekleniyordu.".to_owned(),
+							found,
+							src,
+							bad_bit,
+						))?;
+					}
+				}
+				Op::MakeMap(count) => {
+					let entries = stack.split_off(stack.len() - count * 2);
+					let mut map = Map { value: vec![] };
+					let mut entries = entries.into_iter();
+					while let (Some(key), Some(value)) = (entries.next(), entries.next()) {
+						map.insert(key, value);
+					}
+					stack.push(Object::Map(map));
+				}
+				Op::Format(count) => {
+					let parts = stack.split_off(stack.len() - count);
+					stack.push(Object::from(parts.iter().map(|x| x.to_string()).collect::<String>()));
+				}
+				Op::Get(shape) => {
+					let values = stack.split_off(stack.len() - shape.arity());
+					let container = stack.pop().unwrap();
+					let accessor = Self::accessor(*shape, values.into_iter());
+					// The common cases are answered without building a source for a diagnostic that isn't needed.
+					let found = match (&container, &accessor) {
+						(Object::Map(map), Accessor::Index(key)) => map.get(key).cloned(),
+						(Object::Array(list), Accessor::Index(Object::Number(index)))
+							if index.value >= 0.0 && (index.value as usize) < list.value.len() =>
+						{
+							Some(list.value[index.value as usize].clone())
+						}
+						_ => None,
+					};
+					let value = match found {
+						Some(value) => value,
+						None => {
+							let (src, bad_bit) = self.locate(span);
+							container.get_at(&accessor, src, bad_bit)?
+						}
+					};
+					stack.push(value);
+				}
+				Op::SetPath { slot, path, method } => {
+					let value = stack.pop().unwrap();
+					let mut values = stack.split_off(stack.len() - path.iter().map(|x| x.arity()).sum::<usize>()).into_iter();
+					let path = path
+						.iter()
+						.map(|shape| Self::accessor(*shape, values.by_ref().take(shape.arity()).collect::<Vec<_>>().into_iter()))
+						.collect::<Vec<_>>();
+					let Some(mut container) = frame.slots[*slot].take() else {
+						return Err(self.missing_variable(span));
+					};
+					let (src, bad_bit) = self.locate(span);
+					let outcome = container.assign_at(&path, method, value, src, bad_bit);
+					frame.slots[*slot] = Some(container);
+					outcome?;
+				}
+				Op::ExpectNumber { floor } => match stack.pop().unwrap() {
+					Object::Number(number) if *floor => stack.push(Object::from(number.value.floor())),
+					Object::Number(number) => stack.push(Object::Number(number)),
+					other => {
+						let (src, bad_bit) = self.locate(span);
+						other.expectToBeNumber(src, bad_bit)?;
+					}
+				},
+				Op::ToNumber => {
+					let value = stack.pop().unwrap();
+					stack.push(Object::Number(value.forceIntoNumber()));
+				}
+
+				Op::Jump(target) => pc = *target,
+				Op::JumpIfFalse(target) => {
+					if !stack.pop().unwrap().isTruthy() {
+						pc = *target;
+					}
+				}
+				Op::JumpIfTrue(target) => {
+					if stack.pop().unwrap().isTruthy() {
+						pc = *target;
+					}
+				}
+				Op::RepeatNext { counter, limit, exit } => {
+					let count = frame.number(*counter);
+					if count >= frame.number(*limit) {
+						pc = *exit;
+					} else {
+						frame.set_number(*counter, count + 1.0);
+					}
+				}
+				Op::ForNext {
+					var,
+					current,
+					end,
+					step,
+					exit,
+				} => {
+					if frame.number(*step).floor() < 1.0 {
+						let found = format!("{:?}", frame.slots[*step]);
+						let (src, bad_bit) = self.locate(span);
+						return Err(TipHatası::expected("Adımın sıfırdan büyük bir sayı olması bekleniyordu.".to_owned(), found, src, bad_bit))?;
+					}
+					let index = frame.number(*current);
+					if index >= frame.number(*end) {
+						pc = *exit;
+					} else {
+						frame.set_number(*var, index);
+					}
+				}
+				Op::ForStep { current, step } => frame.set_number(*current, frame.number(*current) + frame.number(*step)),
+				Op::IntoItems => {
+					let items: Array = match stack.pop().unwrap() {
+						// Maps are iterated over their keys.
+						Object::Map(map) => map.keys().into(),
+						other => other.into(),
+					};
+					stack.push(Object::Array(items));
+				}
+				Op::ForInNext {
+					var,
+					items,
+					index,
+					step,
+					exit,
+				} => {
+					if frame.number(*step).floor() < 1.0 {
+						let found = format!("{:?}", frame.slots[*step]);
+						let (src, bad_bit) = self.locate(span);
+						return Err(TipHatası::expected("Adımın sıfırdan büyük bir sayı olması bekleniyordu.".to_owned(), found, src, bad_bit))?;
+					}
+					let position = frame.number(*index) as usize;
+					let item = match &frame.slots[*items] {
+						Some(Object::Array(items)) => items.value.get(position).cloned(),
+						_ => None,
+					};
+					match item {
+						Some(item) => frame.slots[*var] = Some(item),
+						None => pc = *exit,
+					}
+				}
+				Op::ForInStep { index, step } => {
+					let next = frame.number(*index) as usize + frame.number(*step) as usize;
+					frame.set_number(*index, next as f64);
+				}
+
+				Op::Call { binding, argc } => {
+					let args = stack.split_off(stack.len() - argc);
+					let result = self.call(*binding, args, span)?;
+					stack.push(result.unwrap_or(Object::from(false)));
+				}
+				Op::CallModule { name, argc } => {
+					let args = stack.split_off(stack.len() - argc).into_iter().map(Expression::from).collect();
+					let (src, bad_bit) = self.locate(span);
+					let result = self.manager.call_function(self.root_scope, name, args, src, bad_bit)?;
+					stack.push(result.unwrap_or(Object::from(false)));
+				}
+				Op::DefineFunction { function, binding, defaults } => {
+					let mut values = stack.split_off(stack.len() - defaults).into_iter();
+					let defaults = program.functions[*function]
+						.params
+						.iter()
+						.map(|x| if x.has_default { values.next() } else { None })
+						.collect();
+					self.bindings[*binding] = Some(Binding {
+						function: *function,
+						defaults,
+					});
+				}
+				Op::Import { path, namespace } => {
+					let (src, bad_bit) = self.locate(span);
+					ImportModule(path, namespace, self.root_scope, self.manager, src, bad_bit)?;
+				}
+				Op::TryCatch {
+					try_block,
+					catch_block,
+					error_slot,
+					finally_block,
+					on_break,
+					on_continue,
+				} => {
+					let mut outcome = self.execute(*try_block, frame);
+					if let Some(catch_block) = catch_block
+						&& let Err(report) = outcome
+					{
+						if let Some(slot) = error_slot {
+							frame.slots[*slot] = Some(ErrorObject(&report, self.manager.span().line));
+						}
+						outcome = self.execute(*catch_block, frame);
+					}
+					if let Some(finally_block) = finally_block {
+						// `durdur`, `devam et` or `döndür` inside `sonunda` wins over whatever the other blocks did.
+						match self.execute(*finally_block, frame)? {
+							BlockOutput::None => {}
+							other => outcome = Ok(other),
+						}
+					}
+					match (outcome?, on_break, on_continue) {
+						(BlockOutput::None, ..) => {}
+						(BlockOutput::Break, Some(target), _) | (BlockOutput::Continue, _, Some(target)) => pc = *target,
+						(other, ..) => return Ok(other),
+					}
+				}
+				Op::Throw => {
+					let message = stack.pop().unwrap();
+					let (src, bad_bit) = self.locate(span);
+					return Err(FirlatilanHata {
+						src,
+						bad_bit,
+						message: message.to_string(),
+					})?;
+				}
+				Op::Assert => {
+					if !stack.pop().unwrap().isTruthy() {
+						let (src, bad_bit) = self.locate(span);
+						return Err(DogrulamaBasarisiz { src, bad_bit })?;
+					}
+				}
+				Op::AssertEqual => {
+					let right = stack.pop().unwrap();
+					let left = stack.pop().unwrap();
+					if left != right {
+						let (src, bad_bit) = self.locate(span);
+						return Err(EsitDegil {
+							src,
+							bad_bit,
+							left: ast::object(&left),
+							right: ast::object(&right),
+						})?;
+					}
+				}
+				Op::Return => return Ok(BlockOutput::Return(stack.pop().unwrap())),
+				Op::Break => return Ok(BlockOutput::Break),
+				Op::Continue => return Ok(BlockOutput::Continue),
+
+				Op::Print(count) => {
+					self.manager.print(stack.split_off(stack.len() - count));
+				}
+				Op::PrintTypes(count) => {
+					let values = stack.split_off(stack.len() - count);
+					self.manager.print(values.iter().map(|x| format!("Değer: {}, Tip: {}", x, x.get_type())));
+				}
+				Op::Input(data_type) => {
+					let quote = stack.pop().unwrap();
+					let input = Object::from(Input!(quote));
+					stack.push(match data_type {
+						Some(ObjectType::Number) => match input.forceIntoText().value.trim().parse::<f64>() {
+							Ok(number) => Object::from(number),
+							Err(_) => {
+								let (src, bad_bit) = self.locate(span);
+								return Err(TipHatası::expected(
+									"Girdinin bir Sayı olması bekleniyordu.".to_owned(),
+									format!("{:?}", input),
+									src,
+									bad_bit,
+								))?;
+							}
+						},
+						Some(ObjectType::Text) => Object::Text(input.forceIntoText()),
+						Some(ObjectType::Boolean) => Object::Bool(input.forceIntoBool()),
+						_ => input,
+					});
+				}
+				Op::RandomNumber => {
+					let to = stack.pop().unwrap().forceIntoNumber().value.floor() as i64;
+					let from = stack.pop().unwrap().forceIntoNumber().value.floor() as i64;
+					if from > to {
+						let (src, bad_bit) = self.locate(span);
+						return Err(TipHatası::expected(
+							"Alt sınırın üst sınırdan büyük olmaması bekleniyordu.".to_owned(),
+							format!("{from}, {to}"),
+							src,
+							bad_bit,
+						)
+						.into());
+					}
+					stack.push(Object::from(rand::rng().random_range(from..=to) as f64));
+				}
+				Op::RandomLetter => {
+					let letters = LETTERARRAY.chars().collect::<Vec<_>>();
+					stack.push(Object::from(letters[rand::rng().random_range(0..letters.len())].to_string()));
+				}
+				Op::RandomBool => {
+					let chance = stack.pop().unwrap().forceIntoNumber().value / 100.0;
+					stack.push(Object::from(rand::rng().random_bool(chance.clamp(0.0, 1.0))));
+				}
+				Op::Wait(unit) => {
+					let amount = stack.pop().unwrap().forceIntoNumber().value;
+					std::thread::sleep(unit.duration(amount));
+				}
+			}
+		}
+		Ok(BlockOutput::None)
+	}
+
+	/// Runs the function in `binding` on a fresh frame, checking the arguments the way `ScopeManager::call_function` does.
+	fn call(&mut self, binding: Option<usize>, args: Vec<Object>, span: Span) -> miette::Result<Option<Object>> {
+		let program = self.program;
+		let Some(binding) = binding.filter(|x| self.bindings[*x].is_some()) else {
+			let (src, bad_bit) = self.locate(span);
+			return Err(FonksiyonBulunamadı { src, bad_bit })?;
+		};
+		let prototype = &program.functions[self.bindings[binding].as_ref().unwrap().function];
+		if args.len() > prototype.params.len() {
+			let (src, bad_bit) = self.locate(span);
+			return Err(CokFazlaArguman {
+				src,
+				bad_bit,
+				expected: Some(prototype.params.len()),
+				got: Some(args.len()),
+			})?;
+		}
+
+		let mut frame = Frame::new(program, prototype.layout);
+		let mut args = args.into_iter();
+		for (i, param) in prototype.params.iter().enumerate() {
+			let value = match args.next().or_else(|| self.bindings[binding].as_ref().unwrap().defaults[i].clone()) {
+				Some(value) => value,
+				None => {
+					let (src, bad_bit) = self.locate(span);
+					return Err(EksikArguman {
+						src,
+						bad_bit,
+						expected: Some(param.name.clone()),
+					})?;
+				}
+			};
+			if let Some(expected) = &param.data_type
+				&& value.get_type() != *expected
+			{
+				let (src, bad_bit) = self.locate(span);
+				value.expectToBe(expected.clone(), src, bad_bit)?;
+			}
+			frame.slots[i] = Some(value);
+		}
+
+		if self.depth >= self.manager.recursion_limit() {
+			let (src, bad_bit) = self.locate(span);
+			return Err(OzyinelemeHatası {
+				src,
+				bad_bit,
+				function: prototype.name.clone(),
+				limit: self.manager.recursion_limit(),
+			})?;
+		}
+		if StackExhausted() {
+			let (src, bad_bit) = self.locate(span);
+			return Err(YiginTukendi {
+				src,
+				bad_bit,
+				function: prototype.name.clone(),
+				depth: self.depth,
+			})?;
+		}
+
+		self.depth += 1;
+		let output = self.execute(prototype.chunk, &mut frame);
+		self.depth -= 1;
+		match output? {
+			BlockOutput::Return(value) => Ok(Some(value)),
+			_ => Ok(None),
+		}
+	}
+}
@@ -0,0 +1,14 @@
+//! Bytecode engine, selected with `zen run --engine vm`. The tree walker in `process` stays the reference:
+//! the compiler reads the same scopes `LoadSource` fills and the machine has to print what `ExecuteBlock` prints.
+
+pub mod bytecode;
+pub mod compiler;
+pub mod machine;
+
+use crate::util::ScopeManager::ScopeManager;
+
+/// Compiles the code loaded into `root_scope` and runs it.
+pub fn run(manager: &mut ScopeManager, root_scope: usize) -> miette::Result<()> {
+	let program = compiler::Compiler::compile(manager, root_scope);
+	machine::Machine::new(&program, manager, root_scope).run()
+}
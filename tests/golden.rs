@@ -0,0 +1,133 @@
+//! Golden-output tests: every `.zen` program directly in `tests/fixtures` is run through the library and what it
+//! prints, followed by the error it stopped with, is compared to the `.out` file next to it. Every program runs on
+//! both engines and both have to match the same file. Files in subdirectories are only there to be imported.
+//!
+//! `zen test` is checked the same way: the test files in `tests/runner` are run with and without a filter and the
+//! results are compared to the `.out` files there.
+//!
+//! After an intended change in output, `ZEN_BLESS=1 cargo test --test golden` rewrites the `.out` files, programs
+//! from the tree interpreter, the reference engine. The bytecode VM is still checked against them.
+
+#![allow(non_snake_case)]
+
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::Once,
+};
+
+use ZenBackend::{
+	test::run_tests_to,
+	util::{
+		ScopeManager::{INTERPRETER_STACK_SIZE, ScopeManager},
+		process::{Engine, RunSource, Runopts},
+	},
+};
+use miette::{GraphicalReportHandler, GraphicalTheme};
+
+const FIXTURES: &str = "tests/fixtures";
+const RUNNER: &str = "tests/runner";
+
+/// Renders diagnostics without colors and at a fixed width, so that snapshots don't depend on the terminal.
+fn Setup() {
+	static SETUP: Once = Once::new();
+	SETUP.call_once(|| {
+		colored::control::set_override(false);
+		miette::set_hook(Box::new(|_| Box::new(GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor()).with_width(100))))
+			.unwrap();
+	});
+}
+
+fn Bless() -> bool {
+	std::env::var_os("ZEN_BLESS").is_some_and(|x| x != "0")
+}
+
+/// Adds `output` and the error a run ended with the way snapshots store them.
+fn Outcome(mut output: String, result: miette::Result<()>) -> String {
+	if let Err(report) = result {
+		output.push_str(&format!("--- hata ---\n{:?}", report));
+	}
+	output
+}
+
+/// Compares `actual` to the snapshot, recording a failure that names `what` if they differ.
+fn Check(snapshot: &Path, what: &str, actual: &str, failures: &mut Vec<String>) {
+	match fs::read_to_string(snapshot) {
+		Ok(expected) if expected == actual => {}
+		Ok(expected) => failures.push(format!("{what}: çıktı değişti\n--- beklenen ---\n{expected}--- alınan ---\n{actual}")),
+		Err(_) => failures.push(format!("{what}: {} bulunamadı", snapshot.display())),
+	}
+}
+
+fn Report(failures: Vec<String>) {
+	assert!(
+		failures.is_empty(),
+		"{}\n\nÇıktılar bilerek değiştiyse `ZEN_BLESS=1 cargo test --test golden` ile güncelleyin.",
+		failures.join("\n\n")
+	);
+}
+
+/// Runs `path` on `engine` and returns its output the way the snapshot stores it.
+fn Run(path: &Path, engine: Engine) -> String {
+	let filename = path.to_string_lossy().replace('\\', "/");
+	let source = fs::read_to_string(path).unwrap();
+	std::thread::Builder::new()
+		.stack_size(INTERPRETER_STACK_SIZE)
+		.spawn(move || {
+			let mut manager = ScopeManager::new();
+			manager.set_strict(true);
+			let output = manager.capture_output();
+			let opts = Runopts { strict: true, ..Default::default() };
+			let result = RunSource(&mut manager, source, opts, &filename, engine);
+
+			let output = output.borrow().clone();
+			Outcome(output, result)
+		})
+		.unwrap()
+		.join()
+		.unwrap_or_else(|_| format!("{} çalışırken yorumlayıcı çöktü.\n", path.display()))
+}
+
+fn Fixtures() -> Vec<PathBuf> {
+	let mut paths = fs::read_dir(FIXTURES)
+		.unwrap()
+		.map(|entry| entry.unwrap().path())
+		.filter(|path| path.extension().is_some_and(|x| x == "zen"))
+		.collect::<Vec<_>>();
+	paths.sort();
+	paths
+}
+
+#[test]
+fn golden() {
+	Setup();
+	let mut failures = Vec::new();
+	for path in Fixtures() {
+		let snapshot = path.with_extension("out");
+		if Bless() {
+			fs::write(&snapshot, Run(&path, Engine::Tree)).unwrap();
+		}
+		for engine in [Engine::Tree, Engine::Vm] {
+			let actual = Run(&path, engine);
+			Check(&snapshot, &format!("{} ({engine:?})", path.display()), &actual, &mut failures);
+		}
+	}
+	Report(failures);
+}
+
+#[test]
+fn runner() {
+	Setup();
+	let mut failures = Vec::new();
+	for (name, filter) in [("all", None), ("filtered", Some("sayaç"))] {
+		let mut output = Vec::new();
+		let result = run_tests_to(&[RUNNER.to_owned()], filter, &mut output);
+		let actual = Outcome(String::from_utf8(output).unwrap(), result);
+		let snapshot = Path::new(RUNNER).join(name).with_extension("out");
+		if Bless() {
+			fs::write(&snapshot, &actual).unwrap();
+		}
+		Check(&snapshot, &format!("zen test {RUNNER} ({name})"), &actual, &mut failures);
+	}
+	Report(failures);
+}